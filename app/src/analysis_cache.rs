@@ -0,0 +1,233 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use similarity::{ANALYZER_VERSION, AnalyzedFile, Language, analyze_code_as};
+
+use crate::utils::content_hash;
+
+const KEY_LENGTH: usize = 16;
+const SCORES_FILE: &str = "scores.json";
+
+#[derive(Default)]
+pub struct AnalysisCache {
+    dir: Option<PathBuf>,
+    scores: HashMap<String, f32>,
+    warnings: Vec<String>,
+}
+
+impl AnalysisCache {
+    pub fn open(dir: Option<&Path>) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let Some(dir) = dir else {
+            return Ok(AnalysisCache::default());
+        };
+
+        fs::create_dir_all(dir.join("analysis"))?;
+
+        let path = dir.join(SCORES_FILE);
+        let mut warnings = vec![];
+
+        let scores = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                warnings.push(format!("discarded unreadable {}: {e}", path.display()));
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+
+        Ok(AnalysisCache {
+            dir: Some(dir.to_path_buf()),
+            scores,
+            warnings,
+        })
+    }
+
+    #[must_use]
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    #[must_use]
+    pub fn file_key(content: &str, language: Language) -> String {
+        AnalysisCache::versioned_key(ANALYZER_VERSION, content, language)
+    }
+
+    fn versioned_key(version: u32, content: &str, language: Language) -> String {
+        let mut key = content_hash(format!("{version}:{}:{content}", language.name()).as_bytes());
+        key.truncate(KEY_LENGTH);
+        key
+    }
+
+    #[must_use]
+    pub fn combined_key(keys: &[&str]) -> String {
+        let mut key = content_hash(keys.join(":").as_bytes());
+        key.truncate(KEY_LENGTH);
+        key
+    }
+
+    pub fn analyze(&self, content: &str, language: Language) -> Option<AnalyzedFile> {
+        let Some(dir) = &self.dir else {
+            return analyze_code_as(content, language);
+        };
+
+        let path = dir.join("analysis").join(format!(
+            "{}.json",
+            AnalysisCache::file_key(content, language)
+        ));
+
+        if let Some(analyzed) = fs::read_to_string(&path)
+            .ok()
+            .and_then(|c| serde_json::from_str(&c).ok())
+        {
+            return Some(analyzed);
+        }
+
+        let analyzed = analyze_code_as(content, language)?;
+
+        if let Ok(json) = serde_json::to_string(&analyzed) {
+            _ = fs::write(&path, json);
+        }
+
+        Some(analyzed)
+    }
+
+    fn pair_key(method: &str, a: &str, b: &str) -> String {
+        if a <= b {
+            format!("{method}:{a}:{b}")
+        } else {
+            format!("{method}:{b}:{a}")
+        }
+    }
+
+    #[must_use]
+    pub fn score(&self, method: &str, a: &str, b: &str) -> Option<f32> {
+        self.scores
+            .get(&AnalysisCache::pair_key(method, a, b))
+            .copied()
+    }
+
+    /// Saves the scores of this run. Scores of files that are not part of the
+    /// run (`files`) are dropped, so the file only holds the latest assignment.
+    pub fn store_scores(
+        &mut self,
+        method: &str,
+        files: &HashSet<&str>,
+        scores: impl IntoIterator<Item = (String, String, f32)>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+
+        self.scores.retain(|key, _| {
+            let mut parts = key.rsplitn(3, ':');
+            parts.next().is_some_and(|b| files.contains(b))
+                && parts.next().is_some_and(|a| files.contains(a))
+        });
+
+        for (a, b, score) in scores {
+            self.scores
+                .insert(AnalysisCache::pair_key(method, &a, &b), score);
+        }
+
+        fs::write(dir.join(SCORES_FILE), serde_json::to_string(&self.scores)?)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "int main() {\n    int x = 1;\n    return x + 2;\n}\n";
+
+    fn cached_path(dir: &Path, key: &str) -> PathBuf {
+        dir.join("analysis").join(format!("{key}.json"))
+    }
+
+    #[test]
+    fn analyzed_files_are_read_back_until_the_content_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = AnalysisCache::open(Some(dir.path())).unwrap();
+
+        let analyzed = cache.analyze(SOURCE, Language::C).unwrap();
+        let path = cached_path(dir.path(), &AnalysisCache::file_key(SOURCE, Language::C));
+        assert!(path.exists());
+
+        let mut planted = analyzed.clone();
+        planted.discounted = 0.5;
+        fs::write(&path, serde_json::to_string(&planted).unwrap()).unwrap();
+
+        let cache = AnalysisCache::open(Some(dir.path())).unwrap();
+        assert_eq!(cache.analyze(SOURCE, Language::C).unwrap().discounted, 0.5);
+
+        let changed = SOURCE.replace("x + 2", "x + 3");
+        assert_eq!(
+            cache.analyze(&changed, Language::C).unwrap().discounted,
+            0.0
+        );
+        assert_ne!(
+            AnalysisCache::file_key(&changed, Language::C),
+            AnalysisCache::file_key(SOURCE, Language::C)
+        );
+    }
+
+    #[test]
+    fn analyses_from_another_analyzer_version_are_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = AnalysisCache::open(Some(dir.path())).unwrap();
+
+        let old_key = AnalysisCache::versioned_key(ANALYZER_VERSION - 1, SOURCE, Language::C);
+        assert_ne!(old_key, AnalysisCache::file_key(SOURCE, Language::C));
+
+        let mut planted = analyze_code_as(SOURCE, Language::C).unwrap();
+        planted.discounted = 0.5;
+        fs::write(
+            cached_path(dir.path(), &old_key),
+            serde_json::to_string(&planted).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(cache.analyze(SOURCE, Language::C).unwrap().discounted, 0.0);
+    }
+
+    #[test]
+    fn pair_scores_are_order_independent_and_pruned() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut cache = AnalysisCache::open(Some(dir.path())).unwrap();
+
+        let files = HashSet::from(["a", "b", "c"]);
+        cache
+            .store_scores(
+                "combined:1",
+                &files,
+                [
+                    ("a".to_string(), "b".to_string(), 0.25),
+                    ("c".to_string(), "a".to_string(), 0.75),
+                ],
+            )
+            .unwrap();
+
+        let mut cache = AnalysisCache::open(Some(dir.path())).unwrap();
+        assert!(cache.warnings().is_empty());
+        assert_eq!(cache.score("combined:1", "b", "a"), Some(0.25));
+        assert_eq!(cache.score("combined:1", "a", "c"), Some(0.75));
+        assert_eq!(cache.score("winnowing:1", "a", "b"), None);
+
+        let files = HashSet::from(["a", "b", "d"]);
+        cache
+            .store_scores(
+                "combined:1",
+                &files,
+                [("d".to_string(), "b".to_string(), 0.5)],
+            )
+            .unwrap();
+
+        let cache = AnalysisCache::open(Some(dir.path())).unwrap();
+        assert_eq!(cache.score("combined:1", "a", "b"), Some(0.25));
+        assert_eq!(cache.score("combined:1", "b", "d"), Some(0.5));
+        assert_eq!(cache.score("combined:1", "a", "c"), None);
+    }
+}
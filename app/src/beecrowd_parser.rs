@@ -26,7 +26,7 @@ pub fn beecrowd_report_parser(
         .collect::<Result<_, csv::Error>>()?;
 
     for result in results.values_mut() {
-        let student = records.get(&result.student.profile.email_address);
+        let student = records.get(&result.student.email);
 
         match student {
             Some(std) => {
@@ -1,92 +1,159 @@
-use futures_util::StreamExt;
-
-use std::{collections::HashMap, path::Path, sync::Arc, time::Instant};
+use std::{
+    collections::{HashMap, HashSet, hash_map::Entry},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
 use tokio::{
     fs::{self},
-    io::AsyncWriteExt,
     sync::mpsc::Sender,
 };
 
-use classroom::{
-    api::ClassroomApi,
-    models::{Attachment, Student, StudentSubmission, StudentSubmissions, SubmissionState},
-};
 use reporter::{SubmissionError, SubmissionResult};
+use similarity::Language;
+
+use crate::{
+    submission_source::{
+        AttachmentVersion, SourceAttachment, Student, Submission, SubmissionSource,
+    },
+    sync_manifest::{ManifestEntry, SyncManifest, SyncOutcome},
+    utils::content_hash,
+};
 
 pub enum DownloadEvent {
+    Resubmitted(String),
+    Failed(String, String),
+    Unchanged(u64),
     Start(u64),
     Progress(u64),
     End(f32),
 }
 
+#[derive(Debug, Clone)]
+pub struct DownloadOptions {
+    pub incremental: bool,
+    pub languages: Vec<Language>,
+    pub root: PathBuf,
+}
+
+impl Default for DownloadOptions {
+    fn default() -> Self {
+        Self {
+            incremental: false,
+            languages: vec![Language::C],
+            root: PathBuf::from("./submissions"),
+        }
+    }
+}
+
+struct DownloadJob {
+    attachment: SourceAttachment,
+    student: Student,
+    version: AttachmentVersion,
+}
+
+fn record_result(results: &mut HashMap<String, SubmissionResult>, result: SubmissionResult) {
+    match results.entry(result.student.email.clone()) {
+        Entry::Occupied(mut entry) => {
+            let existing = entry.get_mut();
+            existing.solved += result.solved;
+
+            for error in result.errors {
+                let repeated = matches!(error, SubmissionError::Late)
+                    && existing
+                        .errors
+                        .iter()
+                        .any(|e| matches!(e, SubmissionError::Late));
+
+                if !repeated {
+                    existing.errors.push(error);
+                }
+            }
+        }
+        Entry::Vacant(entry) => {
+            entry.insert(result);
+        }
+    }
+}
+
 pub fn validate_submissions(
-    students: &Arc<HashMap<String, Student>>,
-    submissions: StudentSubmissions,
+    students: &HashMap<String, Student>,
+    submissions: Vec<Submission>,
     results: &mut HashMap<String, SubmissionResult>,
-) -> Vec<StudentSubmission> {
+) -> Vec<Submission> {
     submissions
-        .student_submissions
         .into_iter()
         .filter(|s| {
-            if s.state == SubmissionState::TurnedIn {
-                if s.assignment_submission
-                    .as_ref()
-                    .is_some_and(|a| a.attachments.is_some())
-                {
+            if s.turned_in {
+                if !s.attachments.is_empty() {
                     return true;
                 }
 
                 let std = students.get(&s.user_id).unwrap().clone();
 
                 results.insert(
-                    std.profile.email_address.to_string(),
+                    std.email.to_string(),
                     SubmissionResult {
                         student: std,
                         errors: vec![SubmissionError::InvalidSubmission],
                         comments: vec![],
                         solved: 0,
+                        tests: vec![],
                     },
                 );
+
+                return false;
             }
 
             let std = students.get(&s.user_id).unwrap().clone();
 
             results.insert(
-                std.profile.email_address,
+                std.email,
                 SubmissionResult {
                     student: students.get(&s.user_id).unwrap().clone(),
                     errors: vec![SubmissionError::NoSubmission],
                     comments: vec![],
                     solved: 0,
+                    tests: vec![],
                 },
             );
 
             false
         })
-        .collect::<Vec<StudentSubmission>>()
+        .collect::<Vec<Submission>>()
+}
+
+async fn fetch_versions<S: SubmissionSource>(
+    source: &S,
+    submissions: &[Submission],
+) -> HashMap<String, AttachmentVersion> {
+    let attachments: Vec<&SourceAttachment> =
+        submissions.iter().flat_map(|s| &s.attachments).collect();
+
+    let versions =
+        futures::future::join_all(attachments.iter().map(|a| source.attachment_version(a))).await;
+
+    attachments
+        .into_iter()
+        .zip(versions)
+        .map(|(a, v)| (a.id.clone(), v))
+        .collect()
 }
 
-pub async fn download_classroom_submissions(
-    api: Arc<ClassroomApi>,
-    course_id: &str,
+pub async fn download_submissions<S: SubmissionSource + 'static>(
+    source: Arc<S>,
+    offering_id: &str,
     assignment_id: &str,
+    options: &DownloadOptions,
     results: &mut HashMap<String, SubmissionResult>,
     tx: Sender<DownloadEvent>,
 ) -> Result<f32, Box<dyn std::error::Error + Send + Sync>> {
     let started = Instant::now();
 
-    let students = api.list_students(course_id).await?;
-    let students: Arc<HashMap<String, _>> = Arc::new(
-        students
-            .students
-            .into_iter()
-            .map(|s| (s.user_id.clone(), s))
-            .collect(),
-    );
-
-    let submissions = api
-        .get_student_submissions(course_id, assignment_id)
-        .await?;
+    let students = source.list_students(offering_id).await?;
+    let students: HashMap<String, _> = students.into_iter().map(|s| (s.id.clone(), s)).collect();
+
+    let submissions = source.list_submissions(offering_id, assignment_id).await?;
 
     let valid_submissions = validate_submissions(&students, submissions, results);
 
@@ -94,60 +161,248 @@ pub async fn download_classroom_submissions(
         return Err("no valid submissions were downloaded".into());
     }
 
-    tx.send(DownloadEvent::Start(valid_submissions.len() as u64))
-        .await?;
+    let total: usize = valid_submissions.iter().map(|s| s.attachments.len()).sum();
 
-    let path = format!("./submissions/{course_id}/{assignment_id}");
+    tx.send(DownloadEvent::Start(total as u64)).await?;
+
+    let path = format!("{}/{offering_id}/{assignment_id}", options.root.display());
     let dir = Path::new(&path);
 
-    if dir.exists() && dir.is_dir() {
-        fs::remove_dir_all(&path).await?;
+    let previous = if options.incremental {
+        SyncManifest::load(dir)?
+    } else {
+        if dir.exists() && dir.is_dir() {
+            fs::remove_dir_all(&path).await?;
+        }
+
+        SyncManifest::default()
+    };
+
+    let current: HashSet<String> = valid_submissions
+        .iter()
+        .filter_map(|s| students.get(&s.user_id))
+        .map(|s| s.email.clone())
+        .collect();
+
+    // Students synced before whose submission is no longer valid must not
+    // keep their old files around for the similarity check and the judge.
+    let stale: HashSet<&String> = previous
+        .files
+        .values()
+        .map(|e| &e.email)
+        .filter(|email| !current.contains(*email))
+        .collect();
+
+    for email in stale {
+        if dir.join(email).is_dir() {
+            fs::remove_dir_all(dir.join(email)).await?;
+        }
     }
 
+    let versions = if options.incremental {
+        fetch_versions(source.as_ref(), &valid_submissions).await
+    } else {
+        HashMap::new()
+    };
+
+    let mut manifest = SyncManifest::default();
+    let mut previous_hashes: HashMap<String, HashSet<String>> = HashMap::new();
+    let mut failed: HashSet<String> = HashSet::new();
+    let mut unchanged = 0;
     let mut handles = vec![];
+    let languages: Arc<[Language]> = options.languages.clone().into();
 
     fs::create_dir_all(&path).await?;
 
     for submission in valid_submissions {
-        let sub = submission.assignment_submission.unwrap();
-        for att in sub.attachments.unwrap() {
-            let api = Arc::clone(&api);
-            let students = Arc::clone(&students);
+        let student = students.get(&submission.user_id).unwrap().clone();
+        let email = student.email.clone();
+        let late = submission.late;
+        let attachments = submission.attachments;
+
+        let previous_entries = previous.entries_of(&email);
+
+        let is_unchanged = previous_entries.len() == attachments.len()
+            && attachments.iter().all(|a| {
+                versions
+                    .get(&a.id)
+                    .is_some_and(|v| previous.is_unchanged(&a.id, v))
+            })
+            && (dir.join(&email).is_dir()
+                || previous_entries
+                    .iter()
+                    .all(|(_, e)| !matches!(e.outcome, SyncOutcome::Unzipped(_))));
+
+        if is_unchanged {
+            for att in &attachments {
+                let entry = previous.files[&att.id].clone();
+
+                let mut errors: Vec<SubmissionError> = entry.outcome.error().into_iter().collect();
+                errors.extend(
+                    entry
+                        .duplicates
+                        .iter()
+                        .cloned()
+                        .map(SubmissionError::DuplicateFile),
+                );
+
+                if late {
+                    errors.push(SubmissionError::Late);
+                }
+
+                record_result(
+                    results,
+                    SubmissionResult {
+                        student: student.clone(),
+                        errors,
+                        comments: vec![],
+                        solved: entry.outcome.solved(),
+                        tests: vec![],
+                    },
+                );
+
+                manifest.files.insert(att.id.clone(), entry);
+                unchanged += 1;
+
+                tx.send(DownloadEvent::Progress(1)).await?;
+            }
+
+            continue;
+        }
+
+        if !previous_entries.is_empty() {
+            previous_hashes.insert(
+                email.clone(),
+                previous_entries
+                    .iter()
+                    .map(|(_, e)| e.hash.clone())
+                    .collect(),
+            );
+        }
+
+        if dir.join(&email).is_dir() {
+            fs::remove_dir_all(dir.join(&email)).await?;
+        }
+
+        for att in attachments {
+            let job = DownloadJob {
+                version: versions.get(&att.id).cloned().unwrap_or_default(),
+                attachment: att.clone(),
+                student: student.clone(),
+            };
+
+            let source = Arc::clone(&source);
 
             let path = path.clone();
-            let user_id = submission.user_id.clone();
-
-            let tx = tx.clone();
-
-            handles.push(tokio::spawn(async move {
-                worker(
-                    att,
-                    api,
-                    students,
-                    path,
-                    user_id,
-                    submission.late.unwrap_or(false),
-                    tx,
-                )
-                .await
-            }));
+
+            let handle = tokio::spawn(async move { worker(job, source, path).await });
+
+            handles.push((student.clone(), att, late, handle));
         }
     }
 
-    let task_results: Vec<_> = futures::future::join_all(handles)
-        .await
-        .into_iter()
-        .filter_map(|s| match s {
-            Ok(s2) => s2.ok(),
-            Err(_) => None,
+    for (student, att, late, handle) in handles {
+        let failure = match handle.await {
+            Ok(Ok(downloaded)) => {
+                let (outcome, duplicates) =
+                    extract(&downloaded, dir, &student.email, &languages).await?;
+
+                let mut errors: Vec<SubmissionError> = outcome.error().into_iter().collect();
+                errors.extend(
+                    duplicates
+                        .iter()
+                        .cloned()
+                        .map(SubmissionError::DuplicateFile),
+                );
+
+                if late {
+                    errors.push(SubmissionError::Late);
+                }
+
+                tx.send(DownloadEvent::Progress(1)).await?;
+
+                manifest.files.insert(
+                    att.id,
+                    ManifestEntry {
+                        email: student.email.clone(),
+                        checksum: downloaded.version.checksum,
+                        modified_time: downloaded.version.modified_time,
+                        hash: downloaded.hash,
+                        outcome,
+                        duplicates,
+                    },
+                );
+
+                record_result(
+                    results,
+                    SubmissionResult {
+                        student,
+                        errors,
+                        comments: vec![],
+                        solved: outcome.solved(),
+                        tests: vec![],
+                    },
+                );
+
+                continue;
+            }
+            Ok(Err(e)) => e.to_string(),
+            Err(e) => e.to_string(),
+        };
+
+        failed.insert(student.email.clone());
+
+        tx.send(DownloadEvent::Progress(1)).await?;
+        tx.send(DownloadEvent::Failed(
+            student.email.clone(),
+            format!("{}: {failure}", att.title),
+        ))
+        .await?;
+
+        let mut errors = vec![SubmissionError::DownloadFailed(att.title)];
+
+        if late {
+            errors.push(SubmissionError::Late);
+        }
+
+        record_result(
+            results,
+            SubmissionResult {
+                student,
+                errors,
+                comments: vec![],
+                solved: 0,
+                tests: vec![],
+            },
+        );
+    }
+
+    manifest.save(dir)?;
+
+    let mut resubmitted: Vec<&String> = previous_hashes
+        .iter()
+        .filter(|(email, _)| !failed.contains(*email))
+        .filter(|(email, hashes)| {
+            let current: HashSet<String> = manifest
+                .entries_of(email)
+                .iter()
+                .map(|(_, e)| e.hash.clone())
+                .collect();
+
+            current != **hashes
         })
+        .map(|(email, _)| email)
         .collect();
 
-    results.extend(
-        task_results
-            .into_iter()
-            .map(|s| (s.student.profile.email_address.to_string(), s)),
-    );
+    resubmitted.sort();
+
+    for email in resubmitted {
+        tx.send(DownloadEvent::Resubmitted(email.clone())).await?;
+    }
+
+    if unchanged > 0 {
+        tx.send(DownloadEvent::Unchanged(unchanged)).await?;
+    }
 
     let total = Instant::now().duration_since(started).as_secs_f32();
 
@@ -156,66 +411,57 @@ pub async fn download_classroom_submissions(
     Ok(total)
 }
 
-async fn worker(
-    att: Attachment,
-    api: Arc<ClassroomApi>,
-    students: Arc<HashMap<String, Student>>,
+struct Downloaded {
+    zip_path: String,
+    hash: String,
+    is_zip: bool,
+    version: AttachmentVersion,
+}
+
+async fn worker<S: SubmissionSource>(
+    job: DownloadJob,
+    source: Arc<S>,
     path: String,
-    user_id: String,
-    late: bool,
-    tx: Sender<DownloadEvent>,
-) -> Result<SubmissionResult, Box<dyn std::error::Error + Send + Sync>> {
-    let download = api
-        .download_student_submission(&att.drive_file.id)
-        .await
-        .unwrap();
+) -> Result<Downloaded, Box<dyn std::error::Error + Send + Sync>> {
+    let DownloadJob {
+        attachment: att,
+        student,
+        version,
+    } = job;
 
-    let student = students.get(&user_id).unwrap();
+    let bytes = source.fetch_attachment(&att).await?;
 
-    let path_assignment = format!("{}/{}.zip", &path, student.profile.email_address.clone());
+    let zip_path = format!("{path}/{}-{}.zip", student.email, att.id);
 
-    let mut file = fs::File::create(&path_assignment).await.unwrap();
+    fs::write(&zip_path, &bytes).await?;
 
-    let mut stream = download.bytes_stream();
+    Ok(Downloaded {
+        zip_path,
+        hash: content_hash(&bytes),
+        is_zip: bytes.starts_with(b"PK\x03\x04"),
+        version,
+    })
+}
 
-    while let Some(item) = stream.next().await {
-        let chunk = item.unwrap();
-        file.write_all(&chunk).await.unwrap();
+async fn extract(
+    downloaded: &Downloaded,
+    dir: &Path,
+    email: &str,
+    languages: &[Language],
+) -> Result<(SyncOutcome, Vec<String>), Box<dyn std::error::Error + Send + Sync>> {
+    if !downloaded.is_zip {
+        return Ok((SyncOutcome::InvalidZip, vec![]));
     }
 
-    file.flush().await.unwrap();
-
-    let is_zip = match fs::read(&path_assignment).await {
-        Ok(bytes) => bytes.starts_with(b"PK\x03\x04"),
-        Err(_) => false,
-    };
+    let unzipped =
+        crate::utils::unzip_submission(&downloaded.zip_path, &dir.join(email), email, languages)
+            .ok();
 
-    let mut errors = vec![];
-    let mut solved = 0;
-
-    if is_zip {
-        let res = crate::utils::unzip_submission(&path_assignment);
-
-        match res {
-            Ok(num) => solved = num,
-            Err(_) => {
-                errors.push(SubmissionError::ZipError);
-            }
+    match unzipped {
+        Some((num, duplicates)) => {
+            fs::remove_file(&downloaded.zip_path).await?;
+            Ok((SyncOutcome::Unzipped(num), duplicates))
         }
-    } else {
-        errors.push(SubmissionError::InvalidZip);
-    }
-
-    if late {
-        errors.push(SubmissionError::Late);
+        None => Ok((SyncOutcome::ZipError, vec![])),
     }
-
-    tx.send(DownloadEvent::Progress(1)).await?;
-
-    Ok(SubmissionResult {
-        student: student.clone(),
-        errors,
-        comments: vec![],
-        solved,
-    })
 }
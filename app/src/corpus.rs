@@ -0,0 +1,257 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::PathBuf,
+};
+
+use similarity::{ANALYZER_VERSION, AnalyzedFile, Language};
+
+#[derive(Debug, Clone)]
+pub struct CorpusOptions {
+    pub dir: PathBuf,
+    pub course: String,
+    pub assignment: String,
+    pub archive: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CorpusEntry {
+    #[serde(default)]
+    pub analyzer_version: u32,
+    pub student: String,
+    pub file_name: String,
+    pub analyzed: AnalyzedFile,
+}
+
+pub struct ArchivedFile {
+    pub source: String,
+    pub analyzed: AnalyzedFile,
+}
+
+pub type Corpus = HashMap<(String, Language), Vec<ArchivedFile>>;
+
+pub fn load_corpus(
+    options: &CorpusOptions,
+) -> Result<(Corpus, usize), Box<dyn std::error::Error + Send + Sync>> {
+    let mut corpus: Corpus = HashMap::new();
+    let mut stale = 0;
+
+    if !options.dir.is_dir() {
+        return Ok((corpus, stale));
+    }
+
+    for entry in fs::read_dir(&options.dir)? {
+        let course_dir = entry?.path();
+        let course = course_dir
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+
+        let assignment_dir = course_dir.join(&options.assignment);
+
+        if course == options.course || !assignment_dir.is_dir() {
+            continue;
+        }
+
+        for entry in fs::read_dir(&assignment_dir)? {
+            let path = entry?.path();
+
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+
+            let question = path.file_stem().unwrap().to_string_lossy().to_string();
+            let entries: Vec<CorpusEntry> = serde_json::from_str(&fs::read_to_string(&path)?)?;
+
+            for e in entries {
+                if e.analyzer_version != ANALYZER_VERSION {
+                    stale += 1;
+                    continue;
+                }
+
+                corpus
+                    .entry((question.clone(), e.analyzed.language))
+                    .or_default()
+                    .push(ArchivedFile {
+                        source: format!("{course}/{}/{}", options.assignment, e.file_name),
+                        analyzed: e.analyzed,
+                    });
+            }
+        }
+    }
+
+    Ok((corpus, stale))
+}
+
+pub fn archive_submissions(
+    options: &CorpusOptions,
+    entries: Vec<(String, CorpusEntry)>,
+) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    let dir = options.dir.join(&options.course).join(&options.assignment);
+
+    if dir.is_dir() {
+        fs::remove_dir_all(&dir)?;
+    }
+
+    fs::create_dir_all(&dir)?;
+
+    let total = entries.len();
+    let mut questions: BTreeMap<String, Vec<CorpusEntry>> = BTreeMap::new();
+
+    for (question, entry) in entries {
+        questions.entry(question).or_default().push(entry);
+    }
+
+    for (question, entries) in questions {
+        fs::write(
+            dir.join(format!("{question}.json")),
+            serde_json::to_string(&entries)?,
+        )?;
+    }
+
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reporter::{Student, SubmissionError, SubmissionResult};
+    use similarity::analyze_code_as;
+    use std::path::Path;
+
+    use crate::similarity_checker::{SimilarityOptions, similarity_analyzer};
+
+    const SOURCE: &str = r#"
+#include <stdio.h>
+
+int main() {
+    int n, total = 0;
+    scanf("%d", &n);
+    for (int i = 0; i < n; i++) {
+        int x;
+        scanf("%d", &x);
+        if (x % 2 == 0) {
+            total += x;
+        }
+    }
+    printf("%d\n", total);
+    return 0;
+}
+"#;
+
+    fn options(dir: &Path, course: &str) -> CorpusOptions {
+        CorpusOptions {
+            dir: dir.to_path_buf(),
+            course: course.to_string(),
+            assignment: "lab3".to_string(),
+            archive: false,
+        }
+    }
+
+    fn entry(student: &str, analyzer_version: u32) -> (String, CorpusEntry) {
+        (
+            "q1".to_string(),
+            CorpusEntry {
+                analyzer_version,
+                student: student.to_string(),
+                file_name: format!("q1_{student}.c"),
+                analyzed: analyze_code_as(SOURCE, Language::C).unwrap(),
+            },
+        )
+    }
+
+    #[test]
+    fn the_current_offering_is_skipped() {
+        let corpus = tempfile::tempdir().unwrap();
+
+        archive_submissions(
+            &options(corpus.path(), "2025.1"),
+            vec![entry("ana@school.edu", ANALYZER_VERSION)],
+        )
+        .unwrap();
+
+        let (loaded, stale) = load_corpus(&options(corpus.path(), "2025.1")).unwrap();
+        assert!(loaded.is_empty());
+        assert_eq!(stale, 0);
+
+        let (loaded, _) = load_corpus(&options(corpus.path(), "2025.2")).unwrap();
+        let files = &loaded[&("q1".to_string(), Language::C)];
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].source, "2025.1/lab3/q1_ana@school.edu.c");
+    }
+
+    #[test]
+    fn stale_entries_are_counted_and_left_out() {
+        let corpus = tempfile::tempdir().unwrap();
+
+        archive_submissions(
+            &options(corpus.path(), "2024.2"),
+            vec![
+                entry("ana@school.edu", ANALYZER_VERSION - 1),
+                entry("bia@school.edu", 0),
+                entry("caio@school.edu", ANALYZER_VERSION),
+            ],
+        )
+        .unwrap();
+
+        let (loaded, stale) = load_corpus(&options(corpus.path(), "2025.1")).unwrap();
+        assert_eq!(stale, 2);
+        assert_eq!(loaded[&("q1".to_string(), Language::C)].len(), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn archived_offerings_are_matched() {
+        let corpus = tempfile::tempdir().unwrap();
+        let submissions = tempfile::tempdir().unwrap();
+
+        archive_submissions(
+            &options(corpus.path(), "2024.2"),
+            vec![entry("ana@school.edu", ANALYZER_VERSION)],
+        )
+        .unwrap();
+
+        let student = submissions.path().join("bia@school.edu");
+        fs::create_dir_all(&student).unwrap();
+        fs::write(student.join("q1_bia@school.edu.c"), SOURCE).unwrap();
+
+        let mut results = HashMap::from([(
+            "bia@school.edu".to_string(),
+            SubmissionResult {
+                student: Student {
+                    id: "1".to_string(),
+                    email: "bia@school.edu".to_string(),
+                    name: "Bia".to_string(),
+                },
+                comments: Vec::new(),
+                errors: Vec::new(),
+                solved: 1,
+                tests: Vec::new(),
+            },
+        )]);
+
+        let similarity = SimilarityOptions {
+            threshold: 90,
+            corpus: Some(options(corpus.path(), "2025.1")),
+            ..SimilarityOptions::default()
+        };
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel(100);
+        tokio::spawn(async move { while rx.recv().await.is_some() {} });
+
+        similarity_analyzer(submissions.path(), &mut results, &similarity, tx)
+            .await
+            .unwrap();
+
+        let errors = &results["bia@school.edu"].errors;
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            &errors[0],
+            SubmissionError::CorpusMatch(file, source, score, _)
+                if file == "q1_bia@school.edu.c"
+                    && source == "2024.2/lab3/q1_ana@school.edu.c"
+                    && *score > 0.99
+        ));
+    }
+}
@@ -0,0 +1,182 @@
+use serde::Deserialize;
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+    sync::Arc,
+    time::Instant,
+};
+use tokio::sync::mpsc::Sender;
+
+use classroom::{
+    api::ClassroomApi,
+    models::{GradePatch, SubmissionState},
+};
+use reporter::scoring::ScoreBreakdown;
+
+pub enum PublishEvent {
+    Start(u64),
+    Progress(u64),
+    Failed(String, String),
+    End(f32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GradeMode {
+    #[default]
+    Draft,
+    Assigned,
+}
+
+impl FromStr for GradeMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "draft" => Ok(GradeMode::Draft),
+            "assigned" => Ok(GradeMode::Assigned),
+            _ => Err(format!(
+                "unknown grade mode `{s}` (expected draft or assigned)"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GradeChange {
+    pub email: String,
+    pub submission_id: String,
+    pub current_draft: Option<f64>,
+    pub current_assigned: Option<f64>,
+    pub new_grade: f64,
+    pub returnable: bool,
+}
+
+impl GradeChange {
+    #[must_use]
+    pub fn current(&self, mode: GradeMode) -> Option<f64> {
+        match mode {
+            GradeMode::Draft => self.current_draft,
+            GradeMode::Assigned => self.current_assigned,
+        }
+    }
+
+    #[must_use]
+    pub fn is_changed(&self, mode: GradeMode) -> bool {
+        self.current(mode)
+            .is_none_or(|c| (c - self.new_grade).abs() > f64::EPSILON)
+    }
+}
+
+pub async fn plan_grades(
+    api: &ClassroomApi,
+    course_id: &str,
+    assignment_id: &str,
+    scores: &HashMap<String, ScoreBreakdown>,
+) -> Result<Vec<GradeChange>, Box<dyn std::error::Error + Send + Sync>> {
+    let work = api.get_course_work(course_id, assignment_id).await?;
+    let max_points = work
+        .max_points
+        .ok_or("assignment is ungraded (no max points set in Classroom)")?;
+
+    let students: HashMap<String, String> = api
+        .list_students(course_id)
+        .await?
+        .students
+        .into_iter()
+        .map(|s| (s.user_id, s.profile.email_address))
+        .collect();
+
+    let submissions = api
+        .get_student_submissions(course_id, assignment_id)
+        .await?;
+
+    let mut changes: Vec<GradeChange> = submissions
+        .student_submissions
+        .into_iter()
+        .filter_map(|s| {
+            let email = students.get(&s.user_id)?;
+            let score = scores.get(email)?;
+
+            Some(GradeChange {
+                email: email.clone(),
+                submission_id: s.id,
+                current_draft: s.draft_grade,
+                current_assigned: s.assigned_grade,
+                new_grade: (score.score as f64 * max_points * 100.0).round() / 100.0,
+                returnable: s.state == SubmissionState::TurnedIn,
+            })
+        })
+        .collect();
+
+    changes.sort_by(|a, b| a.email.cmp(&b.email));
+
+    Ok(changes)
+}
+
+pub async fn publish_grades(
+    api: Arc<ClassroomApi>,
+    course_id: &str,
+    assignment_id: &str,
+    changes: &[GradeChange],
+    mode: GradeMode,
+    return_submissions: bool,
+    tx: Sender<PublishEvent>,
+) -> Result<f32, Box<dyn std::error::Error + Send + Sync>> {
+    let started = Instant::now();
+
+    let changed: Vec<&GradeChange> = changes.iter().filter(|c| c.is_changed(mode)).collect();
+    let returns: Vec<&GradeChange> = if return_submissions {
+        changes.iter().filter(|c| c.returnable).collect()
+    } else {
+        vec![]
+    };
+
+    tx.send(PublishEvent::Start((changed.len() + returns.len()) as u64))
+        .await?;
+
+    let mut failed = HashSet::new();
+
+    for change in changed {
+        let patch = match mode {
+            GradeMode::Draft => GradePatch {
+                draft_grade: Some(change.new_grade),
+                ..GradePatch::default()
+            },
+            GradeMode::Assigned => GradePatch {
+                draft_grade: Some(change.new_grade),
+                assigned_grade: Some(change.new_grade),
+            },
+        };
+
+        if let Err(e) = api
+            .patch_student_submission_grade(course_id, assignment_id, &change.submission_id, &patch)
+            .await
+        {
+            failed.insert(&change.email);
+            tx.send(PublishEvent::Failed(change.email.clone(), e.to_string()))
+                .await?;
+        }
+
+        tx.send(PublishEvent::Progress(1)).await?;
+    }
+
+    for change in returns {
+        if !failed.contains(&change.email)
+            && let Err(e) = api
+                .return_student_submission(course_id, assignment_id, &change.submission_id)
+                .await
+        {
+            tx.send(PublishEvent::Failed(change.email.clone(), e.to_string()))
+                .await?;
+        }
+
+        tx.send(PublishEvent::Progress(1)).await?;
+    }
+
+    let total_time = Instant::now().duration_since(started).as_secs_f32();
+
+    tx.send(PublishEvent::End(total_time)).await?;
+
+    Ok(total_time)
+}
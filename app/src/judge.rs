@@ -0,0 +1,758 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    process::{ExitStatus, Stdio},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{
+    fs,
+    io::{AsyncRead, AsyncReadExt, AsyncWriteExt},
+    process::Command,
+    sync::{Semaphore, mpsc::Sender},
+};
+
+use crate::utils::{question_of, student_of};
+use reporter::{SubmissionError, SubmissionResult, TestReport, Verdict};
+
+pub enum JudgeEvent {
+    MissingOutput(PathBuf),
+    Start(u64),
+    Progress(u64),
+    End(f32),
+}
+
+#[derive(Debug, Clone)]
+pub struct JudgeOptions {
+    pub compile_command: String,
+    pub tests_dir: PathBuf,
+    pub time_limit: Duration,
+    pub memory_limit_mb: u64,
+    pub compile_time_limit: Duration,
+}
+
+impl Default for JudgeOptions {
+    fn default() -> Self {
+        Self {
+            compile_command: "gcc -O2 -o {out} {src} -lm".to_string(),
+            tests_dir: PathBuf::from("./tests"),
+            time_limit: Duration::from_secs(2),
+            memory_limit_mb: 256,
+            compile_time_limit: Duration::from_secs(30),
+        }
+    }
+}
+
+struct TestCase {
+    input: Vec<u8>,
+    expected: String,
+}
+
+type TestCases = HashMap<String, Vec<TestCase>>;
+
+async fn load_test_cases(
+    dir: &Path,
+) -> Result<(TestCases, Vec<PathBuf>), Box<dyn std::error::Error + Send + Sync>> {
+    let mut cases = HashMap::new();
+    let mut missing = vec![];
+
+    let mut questions = fs::read_dir(dir).await?;
+
+    while let Some(question) = questions.next_entry().await? {
+        let path = question.path();
+
+        if !path.is_dir() {
+            continue;
+        }
+
+        let mut inputs = vec![];
+        let mut entries = fs::read_dir(&path).await?;
+
+        while let Some(entry) = entries.next_entry().await? {
+            let entry = entry.path();
+
+            if entry.extension().and_then(|e| e.to_str()) == Some("in") {
+                inputs.push(entry);
+            }
+        }
+
+        inputs.sort();
+
+        let mut question_cases = vec![];
+
+        for input in inputs {
+            let output = input.with_extension("out");
+
+            if !output.is_file() {
+                missing.push(output);
+                continue;
+            }
+
+            let expected = fs::read_to_string(output).await?;
+
+            question_cases.push(TestCase {
+                input: fs::read(&input).await?,
+                expected,
+            });
+        }
+
+        let name = path.file_name().unwrap().to_string_lossy().to_lowercase();
+        cases.insert(name, question_cases);
+    }
+
+    Ok((cases, missing))
+}
+
+fn normalize(output: &str) -> Vec<&str> {
+    let mut lines: Vec<&str> = output.lines().map(str::trim_end).collect();
+
+    while lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+
+    lines
+}
+
+const OUTPUT_LIMIT: u64 = 8 * 1024 * 1024;
+const DIAGNOSTICS_LIMIT: usize = 64 * 1024;
+
+async fn read_capped<R: AsyncRead + Unpin>(reader: R, limit: u64) -> std::io::Result<Vec<u8>> {
+    let mut buf = vec![];
+    reader.take(limit + 1).read_to_end(&mut buf).await?;
+
+    Ok(buf)
+}
+
+async fn drain_capped<R: AsyncRead + Unpin>(
+    mut reader: R,
+    limit: usize,
+) -> std::io::Result<(Vec<u8>, bool)> {
+    let mut kept = vec![];
+    let mut truncated = false;
+    let mut chunk = [0; 8192];
+
+    loop {
+        let n = reader.read(&mut chunk).await?;
+
+        if n == 0 {
+            return Ok((kept, truncated));
+        }
+
+        let room = limit - kept.len();
+        truncated |= n > room;
+        kept.extend_from_slice(&chunk[..n.min(room)]);
+    }
+}
+
+enum Compilation {
+    Compiled,
+    Failed(String),
+}
+
+async fn compile(
+    command: &str,
+    src: &Path,
+    out: &Path,
+    time_limit: Duration,
+) -> Result<Compilation, Box<dyn std::error::Error + Send + Sync>> {
+    let parts: Vec<String> = command
+        .split_whitespace()
+        .map(|p| {
+            p.replace("{src}", &src.to_string_lossy())
+                .replace("{out}", &out.to_string_lossy())
+        })
+        .collect();
+
+    let (program, args) = parts.split_first().ok_or("empty compile command")?;
+
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    let stderr = child.stderr.take().ok_or("stderr not captured")?;
+
+    let compiled = tokio::time::timeout(time_limit, async {
+        let (diagnostics, status) =
+            tokio::join!(drain_capped(stderr, DIAGNOSTICS_LIMIT), child.wait());
+
+        Ok::<_, std::io::Error>((status?, diagnostics?))
+    })
+    .await;
+
+    Ok(match compiled {
+        Ok(result) => {
+            let (status, (diagnostics, truncated)) = result?;
+
+            if status.success() {
+                Compilation::Compiled
+            } else {
+                let mut diagnostics = String::from_utf8_lossy(&diagnostics).trim_end().to_string();
+
+                if truncated {
+                    diagnostics.push_str("\n[diagnostics truncated]");
+                }
+
+                Compilation::Failed(diagnostics)
+            }
+        }
+        Err(_) => {
+            _ = child.kill().await;
+
+            Compilation::Failed(format!(
+                "compilation timed out after {}s",
+                time_limit.as_secs()
+            ))
+        }
+    })
+}
+
+/// How far the memory limit is raised when a crashed program is run again to
+/// tell a memory limit from any other crash.
+const MEMORY_RETRY_FACTOR: u64 = 4;
+
+enum Execution {
+    Exited(ExitStatus, Vec<u8>),
+    OutputLimitExceeded,
+    TimeLimitExceeded,
+}
+
+async fn execute(
+    binary: &Path,
+    input: &[u8],
+    memory_limit_mb: u64,
+    time_limit: Duration,
+) -> Result<Execution, Box<dyn std::error::Error + Send + Sync>> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(format!(
+            "ulimit -v {} && exec \"$0\"",
+            memory_limit_mb * 1024
+        ))
+        .arg(binary)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    let mut stdin = child.stdin.take().ok_or("stdin not captured")?;
+    let stdout = child.stdout.take().ok_or("stdout not captured")?;
+    let input = input.to_vec();
+
+    tokio::spawn(async move {
+        _ = stdin.write_all(&input).await;
+    });
+
+    let run = tokio::time::timeout(time_limit, async {
+        let stdout = read_capped(stdout, OUTPUT_LIMIT).await?;
+
+        if stdout.len() as u64 > OUTPUT_LIMIT {
+            return Ok::<_, std::io::Error>(None);
+        }
+
+        Ok(Some((child.wait().await?, stdout)))
+    })
+    .await;
+
+    match run {
+        Ok(Ok(Some((status, stdout)))) => Ok(Execution::Exited(status, stdout)),
+        Ok(Ok(None)) => {
+            _ = child.kill().await;
+            Ok(Execution::OutputLimitExceeded)
+        }
+        Ok(Err(e)) => Err(e.into()),
+        Err(_) => {
+            _ = child.kill().await;
+            Ok(Execution::TimeLimitExceeded)
+        }
+    }
+}
+
+async fn run_case(
+    binary: &Path,
+    case: &TestCase,
+    options: &JudgeOptions,
+) -> Result<Verdict, Box<dyn std::error::Error + Send + Sync>> {
+    let execution = execute(
+        binary,
+        &case.input,
+        options.memory_limit_mb,
+        options.time_limit,
+    )
+    .await?;
+
+    let (status, stdout) = match execution {
+        Execution::Exited(status, stdout) => (status, stdout),
+        Execution::OutputLimitExceeded => return Ok(Verdict::OutputLimitExceeded),
+        Execution::TimeLimitExceeded => return Ok(Verdict::TimeLimitExceeded),
+    };
+
+    if !status.success() {
+        // A program over the limit fails to allocate and crashes like any
+        // other, so it is run again with more memory to tell the two apart.
+        let retry = execute(
+            binary,
+            &case.input,
+            options.memory_limit_mb * MEMORY_RETRY_FACTOR,
+            options.time_limit,
+        )
+        .await?;
+
+        return Ok(match retry {
+            Execution::Exited(status, _) if status.success() => Verdict::MemoryLimitExceeded,
+            _ => Verdict::RuntimeError,
+        });
+    }
+
+    let stdout = String::from_utf8_lossy(&stdout);
+
+    if normalize(&stdout) == normalize(&case.expected) {
+        Ok(Verdict::Accepted)
+    } else {
+        Ok(Verdict::WrongAnswer)
+    }
+}
+
+async fn worker(
+    src: PathBuf,
+    cases: Arc<HashMap<String, Vec<TestCase>>>,
+    build_dir: PathBuf,
+    options: Arc<JudgeOptions>,
+    semaphore: Arc<Semaphore>,
+    tx: Sender<JudgeEvent>,
+) -> Result<TestReport, Box<dyn std::error::Error + Send + Sync>> {
+    let _permit = semaphore.acquire().await?;
+
+    let file_name = src.file_name().unwrap().to_string_lossy().to_string();
+    let binary = build_dir.join(src.file_stem().unwrap());
+
+    let compilation = compile(
+        &options.compile_command,
+        &src,
+        &binary,
+        options.compile_time_limit,
+    )
+    .await;
+
+    let mut report = TestReport {
+        file: file_name.clone(),
+        compiled: false,
+        diagnostics: None,
+        verdicts: vec![],
+    };
+
+    match compilation {
+        Ok(Compilation::Compiled) => {
+            report.compiled = true;
+
+            for case in &cases[question_of(&file_name)] {
+                let verdict = run_case(&binary, case, &options)
+                    .await
+                    .unwrap_or(Verdict::JudgeError);
+
+                report.verdicts.push(verdict);
+            }
+        }
+        Ok(Compilation::Failed(diagnostics)) => report.diagnostics = Some(diagnostics),
+        Err(e) => {
+            report.compiled = true;
+            report.diagnostics = Some(e.to_string());
+            report.verdicts.push(Verdict::JudgeError);
+        }
+    }
+
+    tx.send(JudgeEvent::Progress(1)).await?;
+
+    Ok(report)
+}
+
+fn judge_error(src: &Path, error: String) -> TestReport {
+    TestReport {
+        file: src.file_name().unwrap().to_string_lossy().to_string(),
+        compiled: true,
+        diagnostics: Some(error),
+        verdicts: vec![Verdict::JudgeError],
+    }
+}
+
+pub async fn judge_submissions(
+    dir: &Path,
+    results: &mut HashMap<String, SubmissionResult>,
+    options: &JudgeOptions,
+    tx: Sender<JudgeEvent>,
+) -> Result<f32, Box<dyn std::error::Error + Send + Sync>> {
+    let started = Instant::now();
+
+    let (cases, missing) = load_test_cases(&options.tests_dir).await?;
+    let cases = Arc::new(cases);
+
+    for output in missing {
+        tx.send(JudgeEvent::MissingOutput(output)).await?;
+    }
+
+    let mut files = vec![];
+    let mut dirs = fs::read_dir(dir).await?;
+
+    while let Some(dir) = dirs.next_entry().await? {
+        if !dir.path().is_dir() {
+            continue;
+        }
+
+        let mut entries = fs::read_dir(dir.path()).await?;
+
+        while let Some(entry) = entries.next_entry().await? {
+            let entry = entry.path();
+            let file_name = entry.file_name().unwrap().to_string_lossy();
+
+            if entry.extension().and_then(|e| e.to_str()) == Some("c")
+                && cases.contains_key(question_of(&file_name))
+            {
+                files.push(entry);
+            }
+        }
+    }
+
+    tx.send(JudgeEvent::Start(files.len() as u64)).await?;
+
+    let build_dir = tempfile::Builder::new().prefix("subgrader-").tempdir()?;
+
+    let options = Arc::new(options.clone());
+    let parallelism = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let semaphore = Arc::new(Semaphore::new(parallelism));
+
+    let handles: Vec<_> = files
+        .iter()
+        .map(|src| {
+            tokio::spawn(worker(
+                src.clone(),
+                Arc::clone(&cases),
+                build_dir.path().to_path_buf(),
+                Arc::clone(&options),
+                Arc::clone(&semaphore),
+                tx.clone(),
+            ))
+        })
+        .collect();
+
+    for (src, handle) in files.iter().zip(futures::future::join_all(handles).await) {
+        let report = match handle {
+            Ok(Ok(report)) => report,
+            Ok(Err(e)) => judge_error(src, e.to_string()),
+            Err(e) => judge_error(src, e.to_string()),
+        };
+
+        if let Some(r) = results.get_mut(&student_of(src)) {
+            if !report.compiled {
+                r.errors.push(SubmissionError::CompileError(
+                    report.file.clone(),
+                    report.diagnostics.clone().unwrap_or_default(),
+                ));
+            }
+
+            for (i, verdict) in report.verdicts.iter().enumerate() {
+                if *verdict != Verdict::Accepted {
+                    r.errors.push(SubmissionError::FailedTest(
+                        report.file.clone(),
+                        i + 1,
+                        *verdict,
+                    ));
+                }
+            }
+
+            r.tests.push(report);
+        }
+    }
+
+    build_dir.close()?;
+
+    let total_time = Instant::now().duration_since(started).as_secs_f32();
+
+    tx.send(JudgeEvent::End(total_time)).await?;
+
+    Ok(total_time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn script(dir: &Path, name: &str, body: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = dir.join(name);
+        std::fs::write(&path, format!("#!/bin/sh\n{body}\n")).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn cases_without_expected_output_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let question = dir.path().join("Q1");
+        std::fs::create_dir_all(&question).unwrap();
+
+        for (name, content) in [("1.in", "1"), ("1.out", "2"), ("2.in", "3"), ("3.in", "4")] {
+            std::fs::write(question.join(name), content).unwrap();
+        }
+
+        std::fs::write(question.join("3.out"), "5").unwrap();
+
+        let (cases, missing) = load_test_cases(dir.path()).await.unwrap();
+
+        let expected: Vec<&str> = cases["q1"].iter().map(|c| c.expected.as_str()).collect();
+        assert_eq!(expected, ["2", "5"]);
+        assert_eq!(missing, [question.join("2.out")]);
+    }
+
+    #[tokio::test]
+    async fn compile_drains_a_diagnostics_flood() {
+        let dir = tempfile::tempdir().unwrap();
+        let compiler = script(
+            dir.path(),
+            "cc",
+            "head -c 4000000 /dev/zero | tr '\\0' e >&2\nexit 1",
+        );
+
+        let command = format!("{} {{src}} {{out}}", compiler.display());
+        let started = Instant::now();
+
+        let compilation = compile(
+            &command,
+            &dir.path().join("q1.c"),
+            &dir.path().join("q1"),
+            Duration::from_secs(20),
+        )
+        .await
+        .unwrap();
+
+        let Compilation::Failed(diagnostics) = compilation else {
+            panic!("flooding compiler should fail");
+        };
+
+        assert!(started.elapsed() < Duration::from_secs(20));
+        assert!(diagnostics.starts_with("eee"));
+        assert!(diagnostics.ends_with("[diagnostics truncated]"));
+        assert!(diagnostics.len() < DIAGNOSTICS_LIMIT + 64);
+    }
+
+    #[tokio::test]
+    async fn compile_reports_success() {
+        let dir = tempfile::tempdir().unwrap();
+        let compiler = script(dir.path(), "cc", "echo warning >&2");
+
+        let compilation = compile(
+            &format!("{} {{src}}", compiler.display()),
+            &dir.path().join("q1.c"),
+            &dir.path().join("q1"),
+            Duration::from_secs(20),
+        )
+        .await
+        .unwrap();
+
+        assert!(matches!(compilation, Compilation::Compiled));
+    }
+
+    async fn verdict(dir: &Path, program: &str, input: &str, expected: &str) -> Verdict {
+        let binary = script(dir, "program", program);
+
+        let case = TestCase {
+            input: input.as_bytes().to_vec(),
+            expected: expected.to_string(),
+        };
+
+        let options = JudgeOptions {
+            time_limit: Duration::from_secs(1),
+            memory_limit_mb: 64,
+            ..JudgeOptions::default()
+        };
+
+        run_case(&binary, &case, &options).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn matching_output_is_accepted() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let program = "read a b\necho $((a + b))";
+        assert_eq!(
+            verdict(dir.path(), program, "1 2\n", "3\n").await,
+            Verdict::Accepted
+        );
+        assert_eq!(
+            verdict(dir.path(), program, "1 2\n", "4\n").await,
+            Verdict::WrongAnswer
+        );
+    }
+
+    #[tokio::test]
+    async fn trailing_whitespace_is_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let program = "printf '1 2   \\r\\n3\\n\\n\\n'";
+        assert_eq!(
+            verdict(dir.path(), program, "", "1 2\n3").await,
+            Verdict::Accepted
+        );
+        assert_eq!(
+            verdict(dir.path(), program, "", "1  2\n3\n").await,
+            Verdict::WrongAnswer
+        );
+        assert_eq!(
+            verdict(dir.path(), program, "", "\n1 2\n3\n").await,
+            Verdict::WrongAnswer
+        );
+    }
+
+    #[tokio::test]
+    async fn crashes_are_runtime_errors() {
+        let dir = tempfile::tempdir().unwrap();
+
+        assert_eq!(
+            verdict(dir.path(), "echo 3\nexit 1", "", "3\n").await,
+            Verdict::RuntimeError
+        );
+        assert_eq!(
+            verdict(dir.path(), "kill -SEGV $$", "", "").await,
+            Verdict::RuntimeError
+        );
+    }
+
+    #[tokio::test]
+    async fn programs_over_the_memory_limit_are_told_apart() {
+        let dir = tempfile::tempdir().unwrap();
+
+        // Stands in for a program that needs between 64 and 256 MB.
+        let program = "[ \"$(ulimit -v)\" -gt 100000 ] || kill -SEGV $$\necho ok";
+        assert_eq!(
+            verdict(dir.path(), program, "", "ok\n").await,
+            Verdict::MemoryLimitExceeded
+        );
+    }
+
+    #[tokio::test]
+    async fn slow_programs_exceed_the_time_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let started = Instant::now();
+
+        assert_eq!(
+            verdict(dir.path(), "exec sleep 10", "", "").await,
+            Verdict::TimeLimitExceeded
+        );
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn each_student_is_judged_separately() {
+        let dir = tempfile::tempdir().unwrap();
+        let compiler = script(
+            dir.path(),
+            "cc",
+            "grep -q '#error' \"$1\" && { echo \"$1: error\" >&2; exit 1; }\ncp \"$1\" \"$2\" && chmod +x \"$2\"",
+        );
+
+        let tests = dir.path().join("tests/q1");
+        std::fs::create_dir_all(&tests).unwrap();
+        std::fs::write(tests.join("1.in"), "1 2\n").unwrap();
+        std::fs::write(tests.join("1.out"), "3\n").unwrap();
+        std::fs::write(tests.join("2.in"), "2 2\n").unwrap();
+        std::fs::write(tests.join("2.out"), "4\n").unwrap();
+
+        let submissions = dir.path().join("submissions");
+        let programs = [
+            ("ana@school.edu", "read a b\necho $((a + b))"),
+            (
+                "bia@school.edu",
+                "read a b\n[ $a -eq 1 ] && exit 3\necho $((a + b))",
+            ),
+            ("caio@school.edu", "#error unfinished"),
+        ];
+
+        let mut results = HashMap::new();
+
+        for (email, program) in programs {
+            let student = submissions.join(email);
+            std::fs::create_dir_all(&student).unwrap();
+            std::fs::write(
+                student.join(format!("q1_{email}.c")),
+                format!("#!/bin/sh\n{program}\n"),
+            )
+            .unwrap();
+
+            results.insert(
+                email.to_string(),
+                SubmissionResult {
+                    student: reporter::Student {
+                        id: email.to_string(),
+                        email: email.to_string(),
+                        name: email.to_string(),
+                    },
+                    comments: vec![],
+                    errors: vec![],
+                    solved: 1,
+                    tests: vec![],
+                },
+            );
+        }
+
+        let options = JudgeOptions {
+            compile_command: format!("{} {{src}} {{out}}", compiler.display()),
+            tests_dir: dir.path().join("tests"),
+            ..JudgeOptions::default()
+        };
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel(100);
+        tokio::spawn(async move { while rx.recv().await.is_some() {} });
+
+        judge_submissions(&submissions, &mut results, &options, tx)
+            .await
+            .unwrap();
+
+        let ana = &results["ana@school.edu"];
+        assert!(ana.errors.is_empty());
+        assert_eq!(
+            ana.tests[0].verdicts,
+            [Verdict::Accepted, Verdict::Accepted]
+        );
+
+        let bia = &results["bia@school.edu"];
+        assert_eq!(
+            bia.tests[0].verdicts,
+            [Verdict::RuntimeError, Verdict::Accepted]
+        );
+        assert!(matches!(
+            bia.errors[..],
+            [SubmissionError::FailedTest(_, 1, Verdict::RuntimeError)]
+        ));
+
+        let caio = &results["caio@school.edu"];
+        assert!(!caio.tests[0].compiled);
+        assert!(matches!(
+            &caio.errors[..],
+            [SubmissionError::CompileError(file, diagnostics)]
+                if file == "q1_caio@school.edu.c" && diagnostics.ends_with(": error")
+        ));
+    }
+
+    #[tokio::test]
+    async fn endless_output_exceeds_the_output_limit() {
+        let case = TestCase {
+            input: vec![],
+            expected: String::new(),
+        };
+
+        let options = JudgeOptions {
+            time_limit: Duration::from_secs(20),
+            ..JudgeOptions::default()
+        };
+
+        let verdict = run_case(Path::new("/usr/bin/yes"), &case, &options)
+            .await
+            .unwrap();
+
+        assert_eq!(verdict, Verdict::OutputLimitExceeded);
+    }
+}
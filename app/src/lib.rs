@@ -1,4 +1,11 @@
+pub mod analysis_cache;
 pub mod beecrowd_parser;
 pub mod classroom_downloader;
+pub mod corpus;
+pub mod grade_publisher;
+pub mod judge;
+pub mod local_loader;
 pub mod similarity_checker;
+pub mod submission_source;
+pub mod sync_manifest;
 pub mod utils;
@@ -0,0 +1,467 @@
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+use tokio::sync::mpsc::Sender;
+
+use reporter::{SubmissionError, SubmissionResult};
+use similarity::Language;
+
+use crate::{
+    submission_source::{
+        Assignment, Offering, SourceAttachment, SourceResult, Student, Submission, SubmissionSource,
+    },
+    utils::{normalized_name, unzip_submission},
+};
+
+pub enum ImportEvent {
+    Start(u64),
+    Progress(u64),
+    End(f32),
+}
+
+#[derive(Debug, Deserialize)]
+struct RosterRecord {
+    email: String,
+    name: String,
+}
+
+fn local_student(email: &str, name: &str) -> Student {
+    Student {
+        id: email.to_lowercase(),
+        email: email.to_string(),
+        name: name.to_string(),
+    }
+}
+
+pub fn load_roster(
+    path: &Path,
+) -> Result<HashMap<String, Student>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut rdr = csv::Reader::from_path(path)?;
+
+    rdr.deserialize()
+        .map(|r| {
+            let r: RosterRecord = r?;
+            Ok((r.email.to_lowercase(), local_student(&r.email, &r.name)))
+        })
+        .collect::<Result<_, csv::Error>>()
+        .map_err(Into::into)
+}
+
+fn copy_sources(
+    src: &Path,
+    dest: &Path,
+    student: &str,
+    languages: &[Language],
+    duplicates: &mut Vec<String>,
+) -> Result<i32, std::io::Error> {
+    let mut copied = 0;
+
+    for entry in fs::read_dir(src)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            copied += copy_sources(&path, dest, student, languages, duplicates)?;
+            continue;
+        }
+
+        let file_name = path.file_name().unwrap().to_string_lossy();
+
+        if let Some(new_name) = normalized_name(&file_name, student, languages) {
+            let new_path = dest.join(&new_name);
+
+            if new_path.exists() {
+                if !duplicates.contains(&new_name) {
+                    duplicates.push(new_name);
+                }
+
+                continue;
+            }
+
+            fs::copy(&path, new_path)?;
+            copied += 1;
+        }
+    }
+
+    Ok(copied)
+}
+
+fn normalize_sources(
+    src: &Path,
+    dest: &Path,
+    student: &str,
+    languages: &[Language],
+    duplicates: &mut Vec<String>,
+) -> Result<i32, std::io::Error> {
+    let mut normalized = 0;
+    let entries = fs::read_dir(src)?.collect::<Result<Vec<_>, _>>()?;
+
+    for entry in entries {
+        let path = entry.path();
+
+        if path.is_dir() {
+            normalized += normalize_sources(&path, dest, student, languages, duplicates)?;
+            continue;
+        }
+
+        let file_name = path.file_name().unwrap().to_string_lossy();
+
+        if let Some(new_name) = normalized_name(&file_name, student, languages) {
+            let new_path = dest.join(&new_name);
+
+            if new_path != path {
+                if new_path.exists() {
+                    if !duplicates.contains(&new_name) {
+                        duplicates.push(new_name);
+                    }
+
+                    continue;
+                }
+
+                fs::rename(&path, &new_path)?;
+            }
+
+            normalized += 1;
+        }
+    }
+
+    Ok(normalized)
+}
+
+enum ImportError {
+    Io(std::io::Error),
+    Zip,
+}
+
+fn import_entry(
+    path: &Path,
+    dest: &Path,
+    student: &str,
+    languages: &[Language],
+    in_place: bool,
+) -> Result<(i32, Vec<String>), ImportError> {
+    let out_dir = dest.join(student);
+    let mut duplicates = vec![];
+
+    if path.is_dir() {
+        if in_place {
+            let normalized = normalize_sources(path, path, student, languages, &mut duplicates)
+                .map_err(ImportError::Io)?;
+
+            return Ok((normalized, duplicates));
+        }
+
+        if out_dir.is_dir() {
+            fs::remove_dir_all(&out_dir).map_err(ImportError::Io)?;
+        }
+
+        fs::create_dir_all(&out_dir).map_err(ImportError::Io)?;
+
+        let copied = copy_sources(path, &out_dir, student, languages, &mut duplicates)
+            .map_err(ImportError::Io)?;
+
+        return Ok((copied, duplicates));
+    }
+
+    let zip_path = dest.join(format!("{student}.zip"));
+
+    if !in_place {
+        fs::copy(path, &zip_path).map_err(ImportError::Io)?;
+    }
+
+    if out_dir.is_dir() {
+        fs::remove_dir_all(&out_dir).map_err(ImportError::Io)?;
+    }
+
+    let res = unzip_submission(&zip_path.to_string_lossy(), &out_dir, student, languages)
+        .map_err(|_| ImportError::Zip);
+
+    if !in_place {
+        _ = fs::remove_file(&zip_path);
+    }
+
+    res
+}
+
+pub const LOCAL_ID: &str = "local";
+
+pub struct LocalFolder {
+    root: PathBuf,
+    roster: Option<HashMap<String, Student>>,
+}
+
+impl LocalFolder {
+    #[must_use]
+    pub fn new(root: PathBuf, roster: Option<HashMap<String, Student>>) -> Self {
+        Self { root, roster }
+    }
+
+    fn entries(&self) -> std::io::Result<BTreeMap<String, PathBuf>> {
+        let mut entries: BTreeMap<String, PathBuf> = BTreeMap::new();
+
+        for entry in fs::read_dir(&self.root)? {
+            let path = entry?.path();
+
+            if path.is_dir() {
+                let name = path.file_name().unwrap().to_string_lossy().to_string();
+
+                if name != "evidence" {
+                    entries.entry(name).or_insert(path);
+                }
+            } else if path.extension().and_then(|e| e.to_str()) == Some("zip") {
+                let stem = path.file_stem().unwrap().to_string_lossy().to_string();
+                entries.insert(stem, path);
+            }
+        }
+
+        Ok(entries)
+    }
+}
+
+impl SubmissionSource for LocalFolder {
+    async fn list_offerings(&self) -> SourceResult<Vec<Offering>> {
+        Ok(vec![Offering {
+            id: LOCAL_ID.to_string(),
+            name: self.root.display().to_string(),
+        }])
+    }
+
+    async fn list_assignments(&self, _offering_id: &str) -> SourceResult<Vec<Assignment>> {
+        Ok(vec![Assignment {
+            id: LOCAL_ID.to_string(),
+            title: self.root.display().to_string(),
+            max_points: None,
+        }])
+    }
+
+    async fn list_students(&self, _offering_id: &str) -> SourceResult<Vec<Student>> {
+        let mut students: BTreeMap<String, Student> = self
+            .roster
+            .iter()
+            .flat_map(HashMap::values)
+            .map(|s| (s.id.clone(), s.clone()))
+            .collect();
+
+        for email in self.entries()?.keys() {
+            let student = local_student(email, email);
+            students.entry(student.id.clone()).or_insert(student);
+        }
+
+        Ok(students.into_values().collect())
+    }
+
+    async fn list_submissions(
+        &self,
+        _offering_id: &str,
+        _assignment_id: &str,
+    ) -> SourceResult<Vec<Submission>> {
+        let mut submissions: Vec<Submission> = self
+            .entries()?
+            .into_iter()
+            .map(|(email, path)| {
+                let file_name = path.file_name().unwrap().to_string_lossy().to_string();
+
+                Submission {
+                    id: email.clone(),
+                    user_id: email.to_lowercase(),
+                    turned_in: true,
+                    late: false,
+                    attachments: vec![SourceAttachment {
+                        id: file_name.clone(),
+                        title: file_name,
+                    }],
+                }
+            })
+            .collect();
+
+        for student in self.roster.iter().flat_map(HashMap::values) {
+            if !submissions.iter().any(|s| s.user_id == student.id) {
+                submissions.push(Submission {
+                    id: student.email.clone(),
+                    user_id: student.id.clone(),
+                    turned_in: false,
+                    late: false,
+                    attachments: vec![],
+                });
+            }
+        }
+
+        Ok(submissions)
+    }
+
+    async fn fetch_attachment(&self, attachment: &SourceAttachment) -> SourceResult<Vec<u8>> {
+        Ok(tokio::fs::read(self.root.join(&attachment.id)).await?)
+    }
+}
+
+pub async fn import_local_submissions(
+    folder: &LocalFolder,
+    dest: &Path,
+    languages: &[Language],
+    results: &mut HashMap<String, SubmissionResult>,
+    tx: Sender<ImportEvent>,
+) -> Result<f32, Box<dyn std::error::Error + Send + Sync>> {
+    let started = Instant::now();
+
+    fs::create_dir_all(dest)?;
+    let in_place = folder.root.canonicalize()? == dest.canonicalize()?;
+
+    let students: HashMap<String, Student> = folder
+        .list_students(LOCAL_ID)
+        .await?
+        .into_iter()
+        .map(|s| (s.id.clone(), s))
+        .collect();
+    let submissions: Vec<Submission> = folder
+        .list_submissions(LOCAL_ID, LOCAL_ID)
+        .await?
+        .into_iter()
+        .filter(|s| s.turned_in)
+        .collect();
+
+    tx.send(ImportEvent::Start(submissions.len() as u64))
+        .await?;
+
+    for submission in &submissions {
+        let student = students[&submission.user_id].clone();
+        let path = folder.root.join(&submission.attachments[0].id);
+        let email = &submission.id;
+
+        let mut errors = vec![];
+
+        let solved = match import_entry(&path, dest, email, languages, in_place) {
+            Ok((n, duplicates)) => {
+                errors.extend(duplicates.into_iter().map(SubmissionError::DuplicateFile));
+                n
+            }
+            Err(ImportError::Io(e)) => {
+                errors.push(SubmissionError::IoError(e.to_string()));
+                0
+            }
+            Err(ImportError::Zip) => {
+                errors.push(SubmissionError::ZipError);
+                0
+            }
+        };
+
+        results.insert(
+            email.to_lowercase(),
+            SubmissionResult {
+                student,
+                errors,
+                comments: vec![],
+                solved,
+                tests: vec![],
+            },
+        );
+
+        tx.send(ImportEvent::Progress(1)).await?;
+    }
+
+    for student in students.into_values() {
+        results
+            .entry(student.email.to_lowercase())
+            .or_insert_with(|| SubmissionResult {
+                student,
+                errors: vec![SubmissionError::NoSubmission],
+                comments: vec![],
+                solved: 0,
+                tests: vec![],
+            });
+    }
+
+    let total = Instant::now().duration_since(started).as_secs_f32();
+
+    tx.send(ImportEvent::End(total)).await?;
+
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn import(root: &Path, dest: &Path) -> HashMap<String, SubmissionResult> {
+        let folder = LocalFolder::new(root.to_path_buf(), None);
+        let (tx, _rx) = tokio::sync::mpsc::channel(100);
+        let mut results = HashMap::new();
+
+        import_local_submissions(&folder, dest, &[Language::C], &mut results, tx)
+            .await
+            .unwrap();
+
+        results
+    }
+
+    async fn import_in_place(root: &Path) -> HashMap<String, SubmissionResult> {
+        import(root, root).await
+    }
+
+    fn repeated_question(root: &Path) {
+        let student = root.join("jq12@school.edu");
+
+        for folder in ["a", "b", "c"] {
+            fs::create_dir_all(student.join(folder)).unwrap();
+            fs::write(student.join(folder).join("Q1.c"), "int main() {}\n").unwrap();
+        }
+    }
+
+    fn duplicates(result: &SubmissionResult) -> Vec<&str> {
+        result
+            .errors
+            .iter()
+            .filter_map(|e| match e {
+                SubmissionError::DuplicateFile(f) => Some(f.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn repeated_files_are_reported_once_when_copied() {
+        let root = tempfile::tempdir().unwrap();
+        let dest = tempfile::tempdir().unwrap();
+        repeated_question(root.path());
+
+        let results = import(root.path(), dest.path()).await;
+        let result = &results["jq12@school.edu"];
+
+        assert_eq!(result.solved, 1);
+        assert_eq!(duplicates(result), ["q1_jq12@school.edu.c"]);
+    }
+
+    #[tokio::test]
+    async fn repeated_files_are_reported_once_in_place() {
+        let root = tempfile::tempdir().unwrap();
+        repeated_question(root.path());
+
+        let results = import_in_place(root.path()).await;
+        let result = &results["jq12@school.edu"];
+
+        assert_eq!(result.solved, 1);
+        assert_eq!(duplicates(result), ["q1_jq12@school.edu.c"]);
+    }
+
+    #[tokio::test]
+    async fn in_place_imports_can_be_repeated() {
+        let root = tempfile::tempdir().unwrap();
+        let student = root.path().join("jq12@school.edu");
+        fs::create_dir_all(student.join("lista")).unwrap();
+        fs::write(student.join("main.c"), "int main() {}\n").unwrap();
+        fs::write(student.join("lista/Q3.c"), "int main() {}\n").unwrap();
+
+        for _ in 0..2 {
+            let results = import_in_place(root.path()).await;
+            let result = &results["jq12@school.edu"];
+
+            assert_eq!(result.solved, 2);
+            assert!(result.errors.is_empty());
+        }
+
+        assert!(student.join("unknown-number_jq12@school.edu.c").is_file());
+        assert!(student.join("q3_jq12@school.edu.c").is_file());
+    }
+}
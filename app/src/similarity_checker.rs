@@ -1,53 +1,282 @@
 use rayon::prelude::*;
+use serde::Deserialize;
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fs,
+    path::{Path, PathBuf},
+    str::FromStr,
     sync::{Arc, Mutex},
     time::Instant,
 };
 use tokio::sync::mpsc::Sender;
 
-use reporter::{SubmissionError, SubmissionResult};
-use similarity::{AnalyzedFile, analyze_code, compare_two_codes_cached};
+use crate::{
+    analysis_cache::AnalysisCache,
+    corpus::{ArchivedFile, CorpusEntry, CorpusOptions, archive_submissions, load_corpus},
+    utils::{UNKNOWN_QUESTION, question_of, read_source, student_of},
+};
+
+use reporter::{
+    SubmissionError, SubmissionResult,
+    clusters::PlagiarismCluster,
+    evidence::{EvidencePair, generate_evidence},
+};
+use similarity::{
+    ANALYZER_VERSION, AnalyzedFile, Language, SimilarityConfig, SimilarityReport, WinnowingConfig,
+    cluster::clusters,
+    compare_fingerprints, compare_with_config,
+    matching::MIN_FRAGMENT_TOKENS,
+    matching_fragments, subtract_baseline,
+    winnowing::{FingerprintIndex, fingerprint},
+};
 
 pub enum SimilarityEvent {
+    CacheWarning(String),
+    Discounted(String, f32),
+    Clusters(Vec<PlagiarismCluster>),
+    Corpus(u64),
+    StaleCorpus(u64),
+    Archived(u64),
     Start(u64),
     Progress(u64),
     End(f32),
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum UnknownQuestionPolicy {
+    Skip,
+    #[default]
+    SameGroup,
+    CompareAll,
+}
+
+impl FromStr for UnknownQuestionPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "skip" => Ok(UnknownQuestionPolicy::Skip),
+            "same-group" => Ok(UnknownQuestionPolicy::SameGroup),
+            "compare-all" => Ok(UnknownQuestionPolicy::CompareAll),
+            _ => Err(format!(
+                "unknown policy `{s}` (expected skip, same-group or compare-all)"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SimilarityMethod {
+    #[default]
+    Combined,
+    Winnowing,
+}
+
+impl SimilarityMethod {
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            SimilarityMethod::Combined => "combined",
+            SimilarityMethod::Winnowing => "winnowing",
+        }
+    }
+}
+
+impl FromStr for SimilarityMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "combined" => Ok(SimilarityMethod::Combined),
+            "winnowing" => Ok(SimilarityMethod::Winnowing),
+            _ => Err(format!(
+                "unknown method `{s}` (expected combined or winnowing)"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SimilarityOptions {
+    pub threshold: u32,
+    pub unknown_policy: UnknownQuestionPolicy,
+    pub method: SimilarityMethod,
+    pub baseline_dir: Option<PathBuf>,
+    pub corpus: Option<CorpusOptions>,
+    pub cache_dir: Option<PathBuf>,
+    pub scoring: SimilarityConfig,
+    pub winnowing: WinnowingConfig,
+}
+
+struct SubmissionFile {
+    key: String,
+    email: String,
+    file_name: String,
+    content: String,
+    analyzed: AnalyzedFile,
+}
+
+#[derive(Default)]
+struct BaselineSet {
+    keys: Vec<String>,
+    files: Vec<AnalyzedFile>,
+}
+
+type Baselines = HashMap<(String, Language), BaselineSet>;
+
+fn load_baselines(
+    dir: &Path,
+    cache: &AnalysisCache,
+) -> Result<Baselines, Box<dyn std::error::Error + Send + Sync>> {
+    let rg = regex::Regex::new(r"[qQ]\d+")?;
+
+    let mut baselines: Baselines = HashMap::new();
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        let Some(language) = Language::from_path(&path) else {
+            continue;
+        };
+
+        let file_name = path.file_name().unwrap().to_string_lossy();
+        let question = rg
+            .find(&file_name)
+            .map_or(UNKNOWN_QUESTION.to_string(), |m| m.as_str().to_lowercase());
+
+        let content = read_source(&path)?;
+
+        if let Some(analyzed) = cache.analyze(&content, language) {
+            let set = baselines.entry((question, language)).or_default();
+            set.keys.push(AnalysisCache::file_key(&content, language));
+            set.files.push(analyzed);
+        }
+    }
+
+    Ok(baselines)
+}
+
+fn candidate_pairs(
+    ids: &[usize],
+    files: &[SubmissionFile],
+    options: &SimilarityOptions,
+) -> Vec<(usize, usize)> {
+    match options.method {
+        SimilarityMethod::Combined => {
+            let mut pairs = vec![];
+
+            for (i, &a) in ids.iter().enumerate() {
+                for &b in &ids[i + 1..] {
+                    pairs.push((a, b));
+                }
+            }
+
+            pairs
+        }
+        SimilarityMethod::Winnowing => {
+            let mut index = FingerprintIndex::new();
+
+            for &id in ids {
+                index.insert(id, &files[id].analyzed);
+            }
+
+            index.candidate_pairs(&options.winnowing)
+        }
+    }
+}
+
+fn group_pairs(
+    groups: &BTreeMap<String, Vec<usize>>,
+    files: &[SubmissionFile],
+    options: &SimilarityOptions,
+) -> Vec<(usize, usize)> {
+    let mut pairs = vec![];
+
+    for (question, ids) in groups {
+        if question == UNKNOWN_QUESTION && options.unknown_policy == UnknownQuestionPolicy::Skip {
+            continue;
+        }
+
+        pairs.extend(candidate_pairs(ids, files, options));
+    }
+
+    if options.unknown_policy == UnknownQuestionPolicy::CompareAll
+        && let Some(unknown) = groups.get(UNKNOWN_QUESTION)
+    {
+        for (question, ids) in groups {
+            if question == UNKNOWN_QUESTION {
+                continue;
+            }
+
+            let merged: Vec<usize> = unknown.iter().chain(ids).copied().collect();
+
+            pairs.extend(
+                candidate_pairs(&merged, files, options)
+                    .into_iter()
+                    .filter(|(a, b)| unknown.contains(a) != unknown.contains(b)),
+            );
+        }
+    }
+
+    pairs.retain(|(a, b)| {
+        files[*a].analyzed.language == files[*b].analyzed.language
+            && files[*a].email != files[*b].email
+    });
+
+    pairs
+}
+
 pub async fn similarity_analyzer(
-    course_id: &str,
-    assignment_id: &str,
+    dir: &Path,
     results: &mut HashMap<String, SubmissionResult>,
-    threshold: u32,
+    options: &SimilarityOptions,
     tx: Sender<SimilarityEvent>,
 ) -> Result<f32, Box<dyn std::error::Error + Send + Sync>> {
     let started = Instant::now();
 
-    let path_or = format!("./submissions/{course_id}/{assignment_id}");
-
     let mut files = vec![];
 
-    for dir in fs::read_dir(path_or)? {
-        let dir = dir?;
-        let path = dir.path();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
 
         if path.is_dir() {
             for entry in fs::read_dir(&path)? {
-                let entry = entry?;
-                files.push(entry.path());
+                let entry = entry?.path();
+
+                if let Some(language) = Language::from_path(&entry) {
+                    files.push((entry, language));
+                }
             }
         }
     }
 
-    let file_contents: Vec<Arc<(String, String, AnalyzedFile)>> = files
+    let mut cache = AnalysisCache::open(options.cache_dir.as_deref())?;
+
+    for warning in cache.warnings() {
+        tx.send(SimilarityEvent::CacheWarning(warning.clone()))
+            .await?;
+    }
+
+    let baselines = match &options.baseline_dir {
+        Some(dir) => load_baselines(dir, &cache)?,
+        None => HashMap::new(),
+    };
+
+    let sources = files
         .iter()
-        .filter_map(|path| {
-            let content = std::fs::read_to_string(path).unwrap();
+        .map(|(path, language)| Ok((path, language, read_source(path)?)))
+        .collect::<std::io::Result<Vec<_>>>()?;
+
+    // The regions removed by the baseline depend on the k-gram size as well.
+    let winnowing_key = serde_json::to_string(&options.winnowing)?;
+
+    let file_contents: Vec<SubmissionFile> = sources
+        .into_iter()
+        .filter_map(|(path, language, content)| {
             let file_name = path.file_name().unwrap().to_string_lossy();
-            let mut email = file_name.split('_').nth(1).unwrap().to_string();
-            email.truncate(email.len() - 2);
+            let email = student_of(path);
 
             if content.trim().is_empty() {
                 if let Some(r) = results.get_mut(&email) {
@@ -57,28 +286,239 @@ pub async fn similarity_analyzer(
 
                 None
             } else {
-                let analyzed = analyze_code(&content).unwrap();
-                Some(Arc::new((email, file_name.to_string(), analyzed)))
+                let mut analyzed = cache.analyze(&content, *language).unwrap();
+                let mut key = AnalysisCache::file_key(&content, *language);
+
+                if let Some(b) = baselines.get(&(question_of(&file_name).to_string(), *language)) {
+                    analyzed = subtract_baseline(&analyzed, &b.files, &options.winnowing);
+
+                    let mut keys = vec![key.as_str(), winnowing_key.as_str()];
+                    keys.extend(b.keys.iter().map(String::as_str));
+                    key = AnalysisCache::combined_key(&keys);
+                } else if options.winnowing != WinnowingConfig::default() {
+                    analyzed.fingerprints = fingerprint(&analyzed.file_tokens, &options.winnowing);
+                }
+
+                Some(SubmissionFile {
+                    key,
+                    email,
+                    file_name: file_name.to_string(),
+                    content,
+                    analyzed,
+                })
             }
         })
         .collect();
 
+    for file in file_contents.iter().filter(|f| f.analyzed.discounted > 0.0) {
+        tx.send(SimilarityEvent::Discounted(
+            file.file_name.clone(),
+            file.analyzed.discounted,
+        ))
+        .await?;
+    }
+
+    let mut groups: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+
+    for (i, file) in file_contents.iter().enumerate() {
+        groups
+            .entry(question_of(&file.file_name).to_string())
+            .or_default()
+            .push(i);
+    }
+
+    let pairs = group_pairs(&groups, &file_contents, options);
+
+    let (mut corpus, stale) = match &options.corpus {
+        Some(c) => load_corpus(c)?,
+        None => (HashMap::new(), 0),
+    };
+
+    for (key, archived) in &mut corpus {
+        for file in archived {
+            if let Some(b) = baselines.get(key) {
+                file.analyzed = subtract_baseline(&file.analyzed, &b.files, &options.winnowing);
+            } else if options.winnowing != WinnowingConfig::default() {
+                file.analyzed.fingerprints =
+                    fingerprint(&file.analyzed.file_tokens, &options.winnowing);
+            }
+        }
+    }
+
+    if stale > 0 {
+        tx.send(SimilarityEvent::StaleCorpus(stale as u64)).await?;
+    }
+
+    if !corpus.is_empty() {
+        tx.send(SimilarityEvent::Corpus(
+            corpus.values().map(Vec::len).sum::<usize>() as u64,
+        ))
+        .await?;
+    }
+
+    let corpus_pairs: Vec<(usize, &ArchivedFile)> = file_contents
+        .iter()
+        .enumerate()
+        .filter(|(_, f)| {
+            question_of(&f.file_name) != UNKNOWN_QUESTION
+                || options.unknown_policy != UnknownQuestionPolicy::Skip
+        })
+        .flat_map(|(i, f)| {
+            corpus
+                .get(&(question_of(&f.file_name).to_string(), f.analyzed.language))
+                .into_iter()
+                .flatten()
+                .map(move |a| (i, a))
+        })
+        .collect();
+
     let res = Arc::new(Mutex::new(results));
 
     tx.send(SimilarityEvent::Start(
-        ((file_contents.len() * (file_contents.len() - 1)) / 2) as u64,
+        (pairs.len() + corpus_pairs.len()) as u64,
     ))
     .await?;
 
-    (0..file_contents.len())
-        .into_par_iter()
-        .flat_map(|i| {
-            let file_contents = &file_contents;
-            ((i + 1)..file_contents.len())
+    tokio::task::block_in_place(|| {
+        corpus_pairs.into_par_iter().for_each(|(i, archived)| {
+            corpus_worker(
+                Arc::clone(&res),
+                &file_contents[i],
+                archived,
+                options,
+                tx.clone(),
+            )
+        })
+    });
+
+    let method = match options.method {
+        SimilarityMethod::Combined => format!(
+            "{}:{}",
+            options.method.name(),
+            AnalysisCache::combined_key(&[&serde_json::to_string(&options.scoring)?])
+        ),
+        SimilarityMethod::Winnowing => format!(
+            "{}:{}",
+            options.method.name(),
+            AnalysisCache::combined_key(&[&serde_json::to_string(&options.winnowing)?])
+        ),
+    };
+
+    let scored: Vec<(usize, usize, f32, Option<EvidencePair>)> =
+        tokio::task::block_in_place(|| {
+            pairs
                 .into_par_iter()
-                .map(move |j| (Arc::clone(&file_contents[i]), Arc::clone(&file_contents[j])))
+                .map(|(i, j)| {
+                    let (p1, p2) = (&file_contents[i], &file_contents[j]);
+                    let cached = cache.score(&method, &p1.key, &p2.key);
+                    let (score, evidence) =
+                        worker(Arc::clone(&res), p1, p2, cached, options, tx.clone());
+
+                    (i, j, score, evidence)
+                })
+                .collect()
+        });
+
+    let keys = file_contents.iter().map(|f| f.key.as_str()).collect();
+
+    let stored = cache.store_scores(
+        &method,
+        &keys,
+        scored.iter().map(|(i, j, score, _)| {
+            (
+                file_contents[*i].key.clone(),
+                file_contents[*j].key.clone(),
+                *score,
+            )
+        }),
+    );
+
+    if let Err(e) = stored {
+        tx.send(SimilarityEvent::CacheWarning(format!(
+            "could not save pair scores: {e}"
+        )))
+        .await?;
+    }
+
+    let flagged: Vec<(usize, usize, EvidencePair)> = scored
+        .into_iter()
+        .filter_map(|(i, j, _, e)| Some((i, j, e?)))
+        .collect();
+
+    let edges: Vec<(usize, usize, f32)> = flagged
+        .iter()
+        .map(|(i, j, e)| (*i, *j, e.report.score))
+        .collect();
+
+    let rings: Vec<PlagiarismCluster> = clusters(&edges)
+        .into_iter()
+        .map(|c| {
+            let mut questions: Vec<&str> = c
+                .members
+                .iter()
+                .map(|m| question_of(&file_contents[*m].file_name))
+                .collect();
+            questions.sort();
+            questions.dedup();
+
+            PlagiarismCluster {
+                question: questions.join(", "),
+                members: c
+                    .members
+                    .iter()
+                    .map(|m| {
+                        (
+                            file_contents[*m].email.clone(),
+                            file_contents[*m].file_name.clone(),
+                        )
+                    })
+                    .collect(),
+                average: c.average,
+                central: file_contents[c.central].file_name.clone(),
+            }
         })
-        .for_each(|(p1, p2)| worker(Arc::clone(&res), &p1, &p2, threshold, tx.clone()));
+        .collect();
+
+    {
+        let mut lock = res.lock().unwrap();
+
+        for ring in rings.iter().filter(|r| r.members.len() > 2) {
+            for (email, _) in &ring.members {
+                if let Some(r) = lock.get_mut(email) {
+                    r.comments.push(ring.describe());
+                }
+            }
+        }
+    }
+
+    let mut evidence: Vec<EvidencePair> = flagged.into_iter().map(|(_, _, e)| e).collect();
+
+    generate_evidence(&mut evidence, &dir.join("evidence"))?;
+
+    tx.send(SimilarityEvent::Clusters(rings)).await?;
+
+    if let Some(corpus) = options.corpus.as_ref().filter(|c| c.archive) {
+        let entries = file_contents
+            .into_iter()
+            .map(|f| {
+                (
+                    question_of(&f.file_name).to_string(),
+                    CorpusEntry {
+                        analyzer_version: ANALYZER_VERSION,
+                        student: f.email,
+                        analyzed: cache
+                            .analyze(&f.content, f.analyzed.language)
+                            .unwrap_or(f.analyzed),
+                        file_name: f.file_name,
+                    },
+                )
+            })
+            .collect();
+
+        let archived = archive_submissions(corpus, entries)?;
+
+        tx.send(SimilarityEvent::Archived(archived as u64)).await?;
+    }
 
     let total_time = Instant::now().duration_since(started).as_secs_f32();
 
@@ -87,34 +527,216 @@ pub async fn similarity_analyzer(
     Ok(total_time)
 }
 
-fn worker(
+fn similarity_report(
+    method: SimilarityMethod,
+    scoring: &SimilarityConfig,
+    a1: &AnalyzedFile,
+    a2: &AnalyzedFile,
+) -> SimilarityReport {
+    match method {
+        SimilarityMethod::Combined => compare_with_config(a1, a2, scoring),
+        SimilarityMethod::Winnowing => SimilarityReport::from_score(compare_fingerprints(a1, a2)),
+    }
+}
+
+fn corpus_worker(
     results: Arc<Mutex<&mut HashMap<String, SubmissionResult>>>,
-    p1: &Arc<(String, String, AnalyzedFile)>,
-    p2: &Arc<(String, String, AnalyzedFile)>,
-    threshold: u32,
+    file: &SubmissionFile,
+    archived: &ArchivedFile,
+    options: &SimilarityOptions,
     tx: Sender<SimilarityEvent>,
 ) {
-    let res = compare_two_codes_cached(&p1.2, &p2.2);
+    let res = similarity_report(
+        options.method,
+        &options.scoring,
+        &file.analyzed,
+        &archived.analyzed,
+    )
+    .score;
+
+    if res >= options.threshold as f32 / 100.0 {
+        let fragments = matching_fragments(&file.analyzed, &archived.analyzed, MIN_FRAGMENT_TOKENS);
+
+        if let Some(r) = results.lock().unwrap().get_mut(&file.email) {
+            r.errors.push(SubmissionError::CorpusMatch(
+                file.file_name.clone(),
+                archived.source.clone(),
+                res,
+                fragments,
+            ));
+        }
+    }
+
+    tx.blocking_send(SimilarityEvent::Progress(1)).unwrap();
+}
+
+fn worker(
+    results: Arc<Mutex<&mut HashMap<String, SubmissionResult>>>,
+    p1: &SubmissionFile,
+    p2: &SubmissionFile,
+    cached: Option<f32>,
+    options: &SimilarityOptions,
+    tx: Sender<SimilarityEvent>,
+) -> (f32, Option<EvidencePair>) {
+    let mut report = cached.map_or_else(
+        || similarity_report(options.method, &options.scoring, &p1.analyzed, &p2.analyzed),
+        SimilarityReport::from_score,
+    );
+    let res = report.score;
+
+    let mut evidence = None;
+
+    if res >= options.threshold as f32 / 100.0 {
+        if cached.is_some() {
+            report =
+                similarity_report(options.method, &options.scoring, &p1.analyzed, &p2.analyzed);
+        }
+
+        let fragments = matching_fragments(&p1.analyzed, &p2.analyzed, MIN_FRAGMENT_TOKENS);
 
-    if res >= threshold as f32 / 100.0 {
         let mut lock = results.lock().unwrap();
 
-        if let Some(r) = lock.get_mut(&p1.0) {
+        if let Some(r) = lock.get_mut(&p1.email) {
             r.errors.push(SubmissionError::PlagiarismDetected(
-                p1.1.clone(),
-                p2.1.clone(),
-                res,
+                p1.file_name.clone(),
+                p2.file_name.clone(),
+                report.clone(),
+                fragments.clone(),
             ));
         }
 
-        if let Some(r) = lock.get_mut(&p2.0) {
+        if let Some(r) = lock.get_mut(&p2.email) {
             r.errors.push(SubmissionError::PlagiarismDetected(
-                p2.1.clone(),
-                p1.1.clone(),
-                res,
+                p2.file_name.clone(),
+                p1.file_name.clone(),
+                report.mirrored(),
+                fragments.iter().map(|f| f.mirrored()).collect(),
             ));
         }
+
+        evidence = Some(EvidencePair {
+            left_name: p1.file_name.clone(),
+            right_name: p2.file_name.clone(),
+            left_source: p1.content.clone(),
+            right_source: p2.content.clone(),
+            metrics: [
+                ("Combined", SimilarityMethod::Combined),
+                ("Winnowing", SimilarityMethod::Winnowing),
+            ]
+            .into_iter()
+            .map(|(name, method)| {
+                let score = if method == options.method {
+                    res
+                } else {
+                    similarity_report(method, &options.scoring, &p1.analyzed, &p2.analyzed).score
+                };
+
+                (name.to_string(), score)
+            })
+            .collect(),
+            report,
+            fragments,
+        });
     }
 
     tx.blocking_send(SimilarityEvent::Progress(1)).unwrap();
+
+    (res, evidence)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use similarity::analyze_code_as;
+
+    fn file(name: &str, language: Language) -> SubmissionFile {
+        let content = format!(
+            "int {}() {{ return 0; }}\n",
+            name.replace(['.', '@', '-'], "_")
+        );
+
+        SubmissionFile {
+            key: name.to_string(),
+            email: student_of(Path::new(name)),
+            file_name: name.to_string(),
+            analyzed: analyze_code_as(&content, language).unwrap(),
+            content,
+        }
+    }
+
+    fn pairs(files: &[SubmissionFile], policy: UnknownQuestionPolicy) -> Vec<(&str, &str)> {
+        let mut groups: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+
+        for (i, f) in files.iter().enumerate() {
+            groups
+                .entry(question_of(&f.file_name).to_string())
+                .or_default()
+                .push(i);
+        }
+
+        let options = SimilarityOptions {
+            unknown_policy: policy,
+            ..SimilarityOptions::default()
+        };
+
+        let mut pairs: Vec<(&str, &str)> = group_pairs(&groups, files, &options)
+            .into_iter()
+            .map(|(a, b)| {
+                let (a, b) = (files[a].file_name.as_str(), files[b].file_name.as_str());
+                (a.min(b), a.max(b))
+            })
+            .collect();
+        pairs.sort();
+        pairs
+    }
+
+    fn files() -> Vec<SubmissionFile> {
+        vec![
+            file("q1_ana.c", Language::C),
+            file("q1_bia.c", Language::C),
+            file("q1_caio.cpp", Language::Cpp),
+            file("q2_ana.c", Language::C),
+            file("q2_bia.c", Language::C),
+            file("unknown-number_ana.c", Language::C),
+            file("unknown-number_caio.c", Language::C),
+        ]
+    }
+
+    #[test]
+    fn unknown_files_can_be_skipped() {
+        assert_eq!(
+            pairs(&files(), UnknownQuestionPolicy::Skip),
+            vec![("q1_ana.c", "q1_bia.c"), ("q2_ana.c", "q2_bia.c")]
+        );
+    }
+
+    #[test]
+    fn unknown_files_can_form_their_own_group() {
+        assert_eq!(
+            pairs(&files(), UnknownQuestionPolicy::SameGroup),
+            vec![
+                ("q1_ana.c", "q1_bia.c"),
+                ("q2_ana.c", "q2_bia.c"),
+                ("unknown-number_ana.c", "unknown-number_caio.c"),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_files_can_be_compared_with_every_question() {
+        assert_eq!(
+            pairs(&files(), UnknownQuestionPolicy::CompareAll),
+            vec![
+                ("q1_ana.c", "q1_bia.c"),
+                ("q1_ana.c", "unknown-number_caio.c"),
+                ("q1_bia.c", "unknown-number_ana.c"),
+                ("q1_bia.c", "unknown-number_caio.c"),
+                ("q2_ana.c", "q2_bia.c"),
+                ("q2_ana.c", "unknown-number_caio.c"),
+                ("q2_bia.c", "unknown-number_ana.c"),
+                ("q2_bia.c", "unknown-number_caio.c"),
+                ("unknown-number_ana.c", "unknown-number_caio.c"),
+            ]
+        );
+    }
 }
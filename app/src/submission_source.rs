@@ -0,0 +1,169 @@
+use std::future::Future;
+
+use classroom::{
+    api::ClassroomApi,
+    models::{StudentSubmission, SubmissionState},
+};
+
+pub use reporter::Student;
+
+pub type SourceResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+#[derive(Debug, Clone)]
+pub struct Offering {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Assignment {
+    pub id: String,
+    pub title: String,
+    pub max_points: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SourceAttachment {
+    pub id: String,
+    pub title: String,
+}
+
+/// What a source knows about the current version of an attachment, used to
+/// skip attachments that did not change since the last sync.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AttachmentVersion {
+    pub checksum: Option<String>,
+    pub modified_time: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Submission {
+    pub id: String,
+    pub user_id: String,
+    pub turned_in: bool,
+    pub late: bool,
+    pub attachments: Vec<SourceAttachment>,
+}
+
+pub trait SubmissionSource: Send + Sync {
+    fn list_offerings(&self) -> impl Future<Output = SourceResult<Vec<Offering>>> + Send;
+
+    fn list_assignments(
+        &self,
+        offering_id: &str,
+    ) -> impl Future<Output = SourceResult<Vec<Assignment>>> + Send;
+
+    fn list_students(
+        &self,
+        offering_id: &str,
+    ) -> impl Future<Output = SourceResult<Vec<Student>>> + Send;
+
+    fn list_submissions(
+        &self,
+        offering_id: &str,
+        assignment_id: &str,
+    ) -> impl Future<Output = SourceResult<Vec<Submission>>> + Send;
+
+    fn fetch_attachment(
+        &self,
+        attachment: &SourceAttachment,
+    ) -> impl Future<Output = SourceResult<Vec<u8>>> + Send;
+
+    fn attachment_version(
+        &self,
+        _attachment: &SourceAttachment,
+    ) -> impl Future<Output = AttachmentVersion> + Send {
+        async { AttachmentVersion::default() }
+    }
+}
+
+impl From<StudentSubmission> for Submission {
+    fn from(s: StudentSubmission) -> Self {
+        Self {
+            turned_in: s.state == SubmissionState::TurnedIn,
+            late: s.late.unwrap_or(false),
+            attachments: s
+                .assignment_submission
+                .and_then(|a| a.attachments)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|a| SourceAttachment {
+                    id: a.drive_file.id,
+                    title: a.drive_file.title,
+                })
+                .collect(),
+            id: s.id,
+            user_id: s.user_id,
+        }
+    }
+}
+
+impl SubmissionSource for ClassroomApi {
+    async fn list_offerings(&self) -> SourceResult<Vec<Offering>> {
+        Ok(self
+            .list_courses()
+            .await?
+            .courses
+            .into_iter()
+            .map(|c| Offering {
+                id: c.id,
+                name: c.name,
+            })
+            .collect())
+    }
+
+    async fn list_assignments(&self, offering_id: &str) -> SourceResult<Vec<Assignment>> {
+        Ok(self
+            .list_course_works(offering_id)
+            .await?
+            .course_work
+            .into_iter()
+            .map(|w| Assignment {
+                id: w.id,
+                title: w.title,
+                max_points: w.max_points,
+            })
+            .collect())
+    }
+
+    async fn list_students(&self, offering_id: &str) -> SourceResult<Vec<Student>> {
+        Ok(ClassroomApi::list_students(self, offering_id)
+            .await?
+            .students
+            .into_iter()
+            .map(|s| Student {
+                id: s.user_id,
+                email: s.profile.email_address,
+                name: s.profile.name.full_name,
+            })
+            .collect())
+    }
+
+    async fn list_submissions(
+        &self,
+        offering_id: &str,
+        assignment_id: &str,
+    ) -> SourceResult<Vec<Submission>> {
+        Ok(self
+            .get_student_submissions(offering_id, assignment_id)
+            .await?
+            .student_submissions
+            .into_iter()
+            .map(Submission::from)
+            .collect())
+    }
+
+    async fn fetch_attachment(&self, attachment: &SourceAttachment) -> SourceResult<Vec<u8>> {
+        Ok(self.download_student_submission(&attachment.id).await?)
+    }
+
+    async fn attachment_version(&self, attachment: &SourceAttachment) -> AttachmentVersion {
+        self.get_drive_file_metadata(&attachment.id)
+            .await
+            .map(|m| AttachmentVersion {
+                checksum: m.md5_checksum,
+                modified_time: m.modified_time,
+            })
+            .unwrap_or_default()
+    }
+}
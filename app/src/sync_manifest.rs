@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs, path::Path};
+
+use reporter::SubmissionError;
+
+use crate::submission_source::AttachmentVersion;
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SyncOutcome {
+    Unzipped(i32),
+    ZipError,
+    InvalidZip,
+}
+
+impl SyncOutcome {
+    #[must_use]
+    pub fn solved(&self) -> i32 {
+        match self {
+            SyncOutcome::Unzipped(n) => *n,
+            _ => 0,
+        }
+    }
+
+    #[must_use]
+    pub fn error(&self) -> Option<SubmissionError> {
+        match self {
+            SyncOutcome::Unzipped(_) => None,
+            SyncOutcome::ZipError => Some(SubmissionError::ZipError),
+            SyncOutcome::InvalidZip => Some(SubmissionError::InvalidZip),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub email: String,
+    #[serde(default)]
+    pub checksum: Option<String>,
+    #[serde(default)]
+    pub modified_time: Option<String>,
+    pub hash: String,
+    pub outcome: SyncOutcome,
+    #[serde(default)]
+    pub duplicates: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SyncManifest {
+    pub files: HashMap<String, ManifestEntry>,
+}
+
+impl SyncManifest {
+    pub fn load(dir: &Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let path = dir.join(MANIFEST_FILE);
+
+        if !path.exists() {
+            return Ok(SyncManifest::default());
+        }
+
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        fs::write(dir.join(MANIFEST_FILE), serde_json::to_string_pretty(self)?)?;
+
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn entries_of(&self, email: &str) -> Vec<(&String, &ManifestEntry)> {
+        self.files
+            .iter()
+            .filter(|(_, e)| e.email == email)
+            .collect()
+    }
+
+    /// Compares the Drive checksum, or the modification time for files without
+    /// one (Google Docs, Sheets, ...).
+    #[must_use]
+    pub fn is_unchanged(&self, file_id: &str, version: &AttachmentVersion) -> bool {
+        self.files
+            .get(file_id)
+            .is_some_and(|e| match (&version.checksum, &version.modified_time) {
+                (Some(checksum), _) => e.checksum.as_ref() == Some(checksum),
+                (None, Some(modified_time)) => {
+                    e.checksum.is_none() && e.modified_time.as_ref() == Some(modified_time)
+                }
+                (None, None) => false,
+            })
+    }
+}
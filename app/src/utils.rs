@@ -1,15 +1,84 @@
+use sha2::{Digest, Sha256};
 use std::{fs::File, path::Path};
 
-pub fn unzip_submission(zip_path: &str) -> Result<i32, Box<dyn std::error::Error>> {
+use similarity::Language;
+
+pub const UNKNOWN_QUESTION: &str = "unknown-number";
+
+pub fn question_of(file_name: &str) -> &str {
+    file_name.split('_').next().unwrap_or(UNKNOWN_QUESTION)
+}
+
+#[must_use]
+pub fn content_hash(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+pub fn read_source(path: &Path) -> std::io::Result<String> {
+    let bytes = std::fs::read(path)?;
+
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+pub fn student_of(path: &Path) -> String {
+    let stem = path.file_stem().unwrap().to_string_lossy();
+
+    match stem.split_once('_') {
+        Some((_, email)) => email.to_string(),
+        None => stem.to_string(),
+    }
+}
+
+fn is_normalized(stem: &str, student: &str) -> bool {
+    stem.strip_suffix(&format!("_{}", student.to_lowercase()))
+        .is_some_and(|q| {
+            q == UNKNOWN_QUESTION
+                || q.strip_prefix('q')
+                    .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+        })
+}
+
+pub fn normalized_name(file_name: &str, student: &str, languages: &[Language]) -> Option<String> {
+    let name = Path::new(file_name);
+
+    if !Language::from_path(name).is_some_and(|l| languages.contains(&l)) {
+        return None;
+    }
+
+    let extension = name.extension()?.to_str()?.to_lowercase();
+    let stem = name.file_stem()?.to_str()?;
+
+    if is_normalized(stem, student) {
+        return Some(format!("{stem}.{extension}"));
+    }
+
+    let rg = regex::Regex::new(r"[qQ]\d+").unwrap();
+    let q_number = rg.find(stem).map_or(UNKNOWN_QUESTION, |m| m.as_str());
+
+    Some(format!(
+        "{}_{}.{}",
+        q_number.to_lowercase(),
+        student.to_lowercase(),
+        extension
+    ))
+}
+
+pub fn unzip_submission(
+    zip_path: &str,
+    out_dir: &Path,
+    student: &str,
+    languages: &[Language],
+) -> Result<(i32, Vec<String>), Box<dyn std::error::Error>> {
     let zip_file = File::open(zip_path)?;
     let mut archive = zip::ZipArchive::new(zip_file)?;
 
-    let out_dir = zip_path.trim_end_matches(".zip");
     std::fs::create_dir_all(out_dir)?;
 
-    let rg = regex::Regex::new(r"[qQ]\d+")?;
-
     let mut res = 0;
+    let mut duplicates = vec![];
 
     for i in 0..archive.len() {
         let mut file = archive.by_index(i)?;
@@ -23,33 +92,59 @@ pub fn unzip_submission(zip_path: &str) -> Result<i32, Box<dyn std::error::Error
             continue;
         }
 
-        let file_name = Path::new(file.name()).file_name();
-
-        if let Some(name) = file_name {
-            let name = Path::new(name);
-            if name.extension().and_then(|s| s.to_str()) != Some("c") {
-                continue;
-            }
-
-            let name_os = name.to_str().unwrap();
+        let new_name = file_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(|n| normalized_name(n, student, languages));
 
-            let q_number = rg.find(name_os).map_or("unknown-number", |m| m.as_str());
+        if let Some(new_name) = new_name {
+            let out_path = out_dir.join(&new_name);
 
-            let zip_name = Path::new(zip_path)
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or("unknown");
+            if out_path.exists() {
+                if !duplicates.contains(&new_name) {
+                    duplicates.push(new_name);
+                }
 
-            let new_name = format!("{}_{}.c", q_number.to_lowercase(), zip_name.to_lowercase());
+                continue;
+            }
 
-            let out_path = Path::new(out_dir).join(new_name);
             let mut out_file = File::create(&out_path)?;
             std::io::copy(&mut file, &mut out_file)?;
             res += 1;
         }
     }
 
-    std::fs::remove_file(zip_path)?;
+    Ok((res, duplicates))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalized_names_are_left_alone() {
+        let languages = [Language::C];
 
-    Ok(res)
+        assert_eq!(
+            normalized_name("Lista Q3.C", "ana@school.edu", &languages).as_deref(),
+            Some("q3_ana@school.edu.c")
+        );
+
+        for name in ["q3_ana@school.edu.c", "unknown-number_ana@school.edu.c"] {
+            assert_eq!(
+                normalized_name(name, "ana@school.edu", &languages).as_deref(),
+                Some(name)
+            );
+        }
+
+        assert_eq!(
+            normalized_name(
+                "unknown-number_jq12@school.edu.c",
+                "jq12@school.edu",
+                &languages
+            )
+            .as_deref(),
+            Some("unknown-number_jq12@school.edu.c")
+        );
+    }
 }
@@ -0,0 +1,779 @@
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+use app::{
+    classroom_downloader::{
+        DownloadEvent, DownloadOptions, download_submissions, validate_submissions,
+    },
+    local_loader::{LOCAL_ID, LocalFolder},
+    submission_source::{
+        Assignment, Offering, SourceAttachment, SourceResult, Student, Submission, SubmissionSource,
+    },
+    sync_manifest::MANIFEST_FILE,
+};
+use classroom::models::{StudentSubmissions, Students};
+use classroom_mock::{
+    MockResponse, MockServer,
+    fixtures::{drive_document_metadata, drive_metadata, page, student, submission, zip},
+};
+use reporter::{SubmissionError, SubmissionResult};
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+const STUDENTS: &str = "/v1/courses/c1/students";
+const SUBMISSIONS: &str = "/v1/courses/c1/courseWork/w1/studentSubmissions";
+
+fn students() -> Vec<Value> {
+    vec![
+        student("u1", "ana@school.edu", "Ana"),
+        student("u2", "bruno@school.edu", "Bruno"),
+        student("u3", "carla@school.edu", "Carla"),
+    ]
+}
+
+fn roster() -> HashMap<String, Student> {
+    let students: Students = serde_json::from_value(page("students", students(), None)).unwrap();
+
+    students
+        .students
+        .into_iter()
+        .map(|s| {
+            let student = Student {
+                id: s.user_id,
+                email: s.profile.email_address,
+                name: s.profile.name.full_name,
+            };
+
+            (student.id.clone(), student)
+        })
+        .collect()
+}
+
+fn mount_course(server: &MockServer) {
+    let mut students = students();
+    let last = students.split_off(2);
+
+    server.mount(
+        "GET",
+        STUDENTS,
+        MockResponse::json(&page("students", students, Some("next"))),
+    );
+    server.mount(
+        "GET",
+        &format!("{STUDENTS}?pageToken=next"),
+        MockResponse::json(&page("students", last, None)),
+    );
+    server.mount(
+        "GET",
+        SUBMISSIONS,
+        MockResponse::json(&page(
+            "studentSubmissions",
+            vec![
+                submission("s1", "u1", "TURNED_IN", &["f1"]),
+                submission("s2", "u2", "TURNED_IN", &["f2"]),
+                submission("s3", "u3", "CREATED", &[]),
+            ],
+            None,
+        )),
+    );
+}
+
+fn solution_zip() -> Vec<u8> {
+    zip(&[
+        ("lista/Q1.c", "int main() { return 0; }\n"),
+        ("lista/q2.c", "int main() { return 1; }\n"),
+        ("lista/notes.txt", "not code\n"),
+        ("__MACOSX/lista/._Q1.c", ""),
+    ])
+}
+
+async fn download(
+    server: &MockServer,
+    root: &Path,
+    incremental: bool,
+) -> (HashMap<String, SubmissionResult>, Vec<DownloadEvent>) {
+    let options = DownloadOptions {
+        incremental,
+        root: root.to_path_buf(),
+        ..DownloadOptions::default()
+    };
+
+    let (tx, mut rx) = mpsc::channel(100);
+    let mut results = HashMap::new();
+
+    download_submissions(
+        Arc::new(server.api()),
+        "c1",
+        "w1",
+        &options,
+        &mut results,
+        tx,
+    )
+    .await
+    .unwrap();
+
+    let mut events = vec![];
+
+    while let Some(event) = rx.recv().await {
+        events.push(event);
+    }
+
+    (results, events)
+}
+
+#[test]
+fn validate_submissions_keeps_only_turned_in_attachments() {
+    let submissions: StudentSubmissions = serde_json::from_value(page(
+        "studentSubmissions",
+        vec![
+            submission("s1", "u1", "TURNED_IN", &["f1"]),
+            submission("s2", "u2", "TURNED_IN", &[]),
+            submission("s3", "u3", "CREATED", &["f3"]),
+        ],
+        None,
+    ))
+    .unwrap();
+
+    let submissions = submissions
+        .student_submissions
+        .into_iter()
+        .map(Submission::from)
+        .collect();
+
+    let mut results = HashMap::new();
+    let valid = validate_submissions(&roster(), submissions, &mut results);
+
+    assert_eq!(valid.len(), 1);
+    assert_eq!(valid[0].id, "s1");
+    assert!(!results.contains_key("ana@school.edu"));
+    assert!(matches!(
+        results["bruno@school.edu"].errors[..],
+        [SubmissionError::InvalidSubmission]
+    ));
+    assert!(matches!(
+        results["carla@school.edu"].errors[..],
+        [SubmissionError::NoSubmission]
+    ));
+}
+
+#[tokio::test]
+async fn downloads_and_unzips_submissions() {
+    let server = MockServer::start().await.unwrap();
+    let root = tempfile::tempdir().unwrap();
+
+    mount_course(&server);
+    server.mount(
+        "GET",
+        "/drive/v3/files/f1?alt=media",
+        MockResponse::bytes(solution_zip()),
+    );
+    server.mount(
+        "GET",
+        "/drive/v3/files/f2?alt=media",
+        MockResponse::bytes("int main() {}\n"),
+    );
+
+    let (results, _) = download(&server, root.path(), false).await;
+
+    let ana = &results["ana@school.edu"];
+    assert!(ana.errors.is_empty());
+    assert_eq!(ana.solved, 2);
+
+    assert!(matches!(
+        results["bruno@school.edu"].errors[..],
+        [SubmissionError::InvalidZip]
+    ));
+    assert!(matches!(
+        results["carla@school.edu"].errors[..],
+        [SubmissionError::NoSubmission]
+    ));
+
+    let dir = root.path().join("c1/w1");
+    assert!(dir.join("ana@school.edu/q1_ana@school.edu.c").is_file());
+    assert!(dir.join("ana@school.edu/q2_ana@school.edu.c").is_file());
+    assert!(!dir.join("ana@school.edu.zip").exists());
+    assert!(dir.join(MANIFEST_FILE).is_file());
+
+    let pages = server.requests_to(STUDENTS);
+    assert_eq!(pages.len(), 2);
+    assert_eq!(pages[1].param("pageToken"), Some("next"));
+
+    assert!(
+        server
+            .requests()
+            .iter()
+            .all(|r| r.authorization.as_deref() == Some("Bearer mock-token"))
+    );
+}
+
+#[tokio::test]
+async fn retries_rate_limited_and_failed_downloads() {
+    let server = MockServer::start().await.unwrap();
+    let root = tempfile::tempdir().unwrap();
+
+    mount_course(&server);
+
+    let media = "/drive/v3/files/f1?alt=media";
+    server.mount(
+        "GET",
+        media,
+        MockResponse::error(429, "rateLimitExceeded", "Quota exceeded")
+            .with_header("Retry-After", "0"),
+    );
+    server.mount(
+        "GET",
+        media,
+        MockResponse::error(503, "backendError", "Backend Error"),
+    );
+    server.mount("GET", media, MockResponse::bytes(solution_zip()));
+    server.mount(
+        "GET",
+        "/drive/v3/files/f2?alt=media",
+        MockResponse::bytes(solution_zip()),
+    );
+
+    let (results, _) = download(&server, root.path(), false).await;
+
+    assert!(results["ana@school.edu"].errors.is_empty());
+    assert_eq!(server.requests_to("/drive/v3/files/f1").len(), 3);
+}
+
+#[tokio::test]
+async fn caps_retry_after_dates_at_the_maximum_delay() {
+    let server = MockServer::start().await.unwrap();
+    let root = tempfile::tempdir().unwrap();
+
+    mount_course(&server);
+
+    let media = "/drive/v3/files/f1?alt=media";
+    server.mount(
+        "GET",
+        media,
+        MockResponse::error(429, "rateLimitExceeded", "Quota exceeded")
+            .with_header("Retry-After", "Fri, 31 Dec 2100 23:59:59 GMT"),
+    );
+    server.mount("GET", media, MockResponse::bytes(solution_zip()));
+    server.mount(
+        "GET",
+        "/drive/v3/files/f2?alt=media",
+        MockResponse::bytes(solution_zip()),
+    );
+
+    let (results, _) = tokio::time::timeout(
+        std::time::Duration::from_secs(10),
+        download(&server, root.path(), false),
+    )
+    .await
+    .unwrap();
+
+    assert!(results["ana@school.edu"].errors.is_empty());
+    assert_eq!(server.requests_to("/drive/v3/files/f1").len(), 2);
+}
+
+#[tokio::test]
+async fn retries_downloads_interrupted_mid_body() {
+    let server = MockServer::start().await.unwrap();
+    let root = tempfile::tempdir().unwrap();
+
+    mount_course(&server);
+
+    let media = "/drive/v3/files/f1?alt=media";
+    server.mount(
+        "GET",
+        media,
+        MockResponse::bytes(solution_zip()).truncated(10),
+    );
+    server.mount("GET", media, MockResponse::bytes(solution_zip()));
+    server.mount(
+        "GET",
+        "/drive/v3/files/f2?alt=media",
+        MockResponse::bytes(solution_zip()),
+    );
+
+    let (results, _) = download(&server, root.path(), false).await;
+
+    assert!(results["ana@school.edu"].errors.is_empty());
+    assert_eq!(results["ana@school.edu"].solved, 2);
+    assert_eq!(server.requests_to("/drive/v3/files/f1").len(), 2);
+}
+
+#[tokio::test]
+async fn reports_files_repeated_across_attachments() {
+    let server = MockServer::start().await.unwrap();
+    let root = tempfile::tempdir().unwrap();
+
+    server.mount(
+        "GET",
+        STUDENTS,
+        MockResponse::json(&page("students", students(), None)),
+    );
+    server.mount(
+        "GET",
+        SUBMISSIONS,
+        MockResponse::json(&page(
+            "studentSubmissions",
+            vec![submission("s1", "u1", "TURNED_IN", &["f1", "f3"])],
+            None,
+        )),
+    );
+    server.mount(
+        "GET",
+        "/drive/v3/files/f1?alt=media",
+        MockResponse::bytes(solution_zip()),
+    );
+    server.mount(
+        "GET",
+        "/drive/v3/files/f3?alt=media",
+        MockResponse::bytes(zip(&[
+            ("q1.c", "int main() { return 3; }\n"),
+            ("q3.c", "int main() { return 3; }\n"),
+        ])),
+    );
+
+    let (results, _) = download(&server, root.path(), false).await;
+
+    let ana = &results["ana@school.edu"];
+    assert_eq!(ana.solved, 3);
+    assert!(matches!(
+        &ana.errors[..],
+        [SubmissionError::DuplicateFile(file)] if file == "q1_ana@school.edu.c"
+    ));
+
+    let q1 = root.path().join("c1/w1/ana@school.edu/q1_ana@school.edu.c");
+    assert_eq!(
+        std::fs::read_to_string(q1).unwrap(),
+        "int main() { return 0; }\n"
+    );
+}
+
+#[tokio::test]
+async fn records_failed_downloads_and_keeps_every_attachment() {
+    let server = MockServer::start().await.unwrap();
+    let root = tempfile::tempdir().unwrap();
+
+    server.mount(
+        "GET",
+        STUDENTS,
+        MockResponse::json(&page("students", students(), None)),
+    );
+    server.mount(
+        "GET",
+        SUBMISSIONS,
+        MockResponse::json(&page(
+            "studentSubmissions",
+            vec![
+                submission("s1", "u1", "TURNED_IN", &["f1", "f3"]),
+                submission("s2", "u2", "TURNED_IN", &["f2"]),
+                submission("s3", "u3", "CREATED", &[]),
+            ],
+            None,
+        )),
+    );
+    server.mount(
+        "GET",
+        "/drive/v3/files/f1?alt=media",
+        MockResponse::bytes(solution_zip()),
+    );
+    server.mount(
+        "GET",
+        "/drive/v3/files/f3?alt=media",
+        MockResponse::bytes(zip(&[("q3.c", "int main() { return 3; }\n")])),
+    );
+    server.mount(
+        "GET",
+        "/drive/v3/files/f2?alt=media",
+        MockResponse::error(503, "backendError", "Backend Error"),
+    );
+
+    let (results, events) = download(&server, root.path(), false).await;
+
+    let ana = &results["ana@school.edu"];
+    assert!(ana.errors.is_empty());
+    assert_eq!(ana.solved, 3);
+
+    let dir = root.path().join("c1/w1/ana@school.edu");
+    assert!(dir.join("q1_ana@school.edu.c").is_file());
+    assert!(dir.join("q3_ana@school.edu.c").is_file());
+
+    assert!(matches!(
+        &results["bruno@school.edu"].errors[..],
+        [SubmissionError::DownloadFailed(title)] if title == "f2.zip"
+    ));
+    assert!(
+        events
+            .iter()
+            .any(|e| matches!(e, DownloadEvent::Failed(email, _) if email == "bruno@school.edu"))
+    );
+    assert_eq!(server.requests_to("/drive/v3/files/f2").len(), 6);
+}
+
+#[tokio::test]
+async fn incremental_sync_skips_unchanged_attachments() {
+    let server = MockServer::start().await.unwrap();
+    let root = tempfile::tempdir().unwrap();
+
+    mount_course(&server);
+
+    for id in ["f1", "f2"] {
+        server.mount(
+            "GET",
+            &format!("/drive/v3/files/{id}?alt=media"),
+            MockResponse::bytes(solution_zip()),
+        );
+        server.mount(
+            "GET",
+            &format!("/drive/v3/files/{id}"),
+            MockResponse::json(&drive_metadata(id, "9a0364b9e99bb480dd25e1f0284c8555")),
+        );
+    }
+
+    download(&server, root.path(), true).await;
+    let (results, events) = download(&server, root.path(), true).await;
+
+    assert_eq!(results["bruno@school.edu"].solved, 2);
+    assert!(
+        events
+            .iter()
+            .any(|e| matches!(e, DownloadEvent::Unchanged(2)))
+    );
+
+    let downloads = server
+        .requests_to("/drive/v3/files/f1")
+        .iter()
+        .filter(|r| r.param("alt") == Some("media"))
+        .count();
+    assert_eq!(downloads, 1);
+}
+
+#[tokio::test]
+async fn incremental_sync_removes_withdrawn_submissions() {
+    let server = MockServer::start().await.unwrap();
+    let root = tempfile::tempdir().unwrap();
+
+    mount_course(&server);
+    server.mount(
+        "GET",
+        SUBMISSIONS,
+        MockResponse::json(&page(
+            "studentSubmissions",
+            vec![
+                submission("s1", "u1", "CREATED", &[]),
+                submission("s2", "u2", "TURNED_IN", &["f2"]),
+                submission("s3", "u3", "CREATED", &[]),
+            ],
+            None,
+        )),
+    );
+
+    for id in ["f1", "f2"] {
+        server.mount(
+            "GET",
+            &format!("/drive/v3/files/{id}?alt=media"),
+            MockResponse::bytes(solution_zip()),
+        );
+        server.mount(
+            "GET",
+            &format!("/drive/v3/files/{id}"),
+            MockResponse::json(&drive_metadata(id, "9a0364b9e99bb480dd25e1f0284c8555")),
+        );
+    }
+
+    let dir = root.path().join("c1/w1");
+
+    download(&server, root.path(), true).await;
+    assert!(dir.join("ana@school.edu").is_dir());
+
+    let (results, _) = download(&server, root.path(), true).await;
+
+    assert!(matches!(
+        &results["ana@school.edu"].errors[..],
+        [SubmissionError::NoSubmission]
+    ));
+    assert!(!dir.join("ana@school.edu").exists());
+    assert!(dir.join("bruno@school.edu").is_dir());
+    assert_eq!(results["bruno@school.edu"].solved, 2);
+}
+
+#[tokio::test]
+async fn incremental_sync_falls_back_to_modification_times() {
+    let server = MockServer::start().await.unwrap();
+    let root = tempfile::tempdir().unwrap();
+
+    mount_course(&server);
+
+    for (id, second) in [
+        ("f1", "2026-03-01T12:00:00Z"),
+        ("f2", "2026-03-02T09:30:00Z"),
+    ] {
+        server.mount(
+            "GET",
+            &format!("/drive/v3/files/{id}?alt=media"),
+            MockResponse::bytes(solution_zip()),
+        );
+        server.mount(
+            "GET",
+            &format!("/drive/v3/files/{id}"),
+            MockResponse::json(&drive_document_metadata(id, "2026-03-01T12:00:00Z")),
+        );
+        server.mount(
+            "GET",
+            &format!("/drive/v3/files/{id}"),
+            MockResponse::json(&drive_document_metadata(id, second)),
+        );
+    }
+
+    download(&server, root.path(), true).await;
+    let (_, events) = download(&server, root.path(), true).await;
+
+    assert!(
+        events
+            .iter()
+            .any(|e| matches!(e, DownloadEvent::Unchanged(1)))
+    );
+
+    let downloads = |id: &str| {
+        server
+            .requests_to(&format!("/drive/v3/files/{id}"))
+            .iter()
+            .filter(|r| r.param("alt") == Some("media"))
+            .count()
+    };
+    assert_eq!(downloads("f1"), 1);
+    assert_eq!(downloads("f2"), 2);
+
+    let manifest: Value = serde_json::from_str(
+        &std::fs::read_to_string(root.path().join("c1/w1").join(MANIFEST_FILE)).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        manifest["files"]["f2"]["modified_time"],
+        "2026-03-02T09:30:00Z"
+    );
+}
+
+fn mount_resync(server: &MockServer, second_download: MockResponse) {
+    mount_course(server);
+
+    server.mount(
+        "GET",
+        "/drive/v3/files/f1?alt=media",
+        MockResponse::bytes(solution_zip()),
+    );
+    server.mount("GET", "/drive/v3/files/f1?alt=media", second_download);
+    server.mount(
+        "GET",
+        "/drive/v3/files/f1",
+        MockResponse::json(&drive_metadata("f1", "9a0364b9e99bb480dd25e1f0284c8555")),
+    );
+    server.mount(
+        "GET",
+        "/drive/v3/files/f1",
+        MockResponse::json(&drive_metadata("f1", "0cc175b9c0f1b6a831c399e269772661")),
+    );
+    server.mount(
+        "GET",
+        "/drive/v3/files/f2?alt=media",
+        MockResponse::bytes(solution_zip()),
+    );
+    server.mount(
+        "GET",
+        "/drive/v3/files/f2",
+        MockResponse::json(&drive_metadata("f2", "9a0364b9e99bb480dd25e1f0284c8555")),
+    );
+}
+
+#[tokio::test]
+async fn incremental_sync_compares_checksums() {
+    let server = MockServer::start().await.unwrap();
+    let root = tempfile::tempdir().unwrap();
+
+    mount_resync(
+        &server,
+        MockResponse::bytes(zip(&[("q3.c", "int main() { return 3; }\n")])),
+    );
+
+    download(&server, root.path(), true).await;
+    let (results, events) = download(&server, root.path(), true).await;
+
+    assert_eq!(results["ana@school.edu"].solved, 1);
+    assert!(
+        events
+            .iter()
+            .any(|e| matches!(e, DownloadEvent::Resubmitted(email) if email == "ana@school.edu"))
+    );
+    assert!(
+        events
+            .iter()
+            .any(|e| matches!(e, DownloadEvent::Unchanged(1)))
+    );
+}
+
+#[tokio::test]
+async fn failed_downloads_are_not_resubmissions() {
+    let server = MockServer::start().await.unwrap();
+    let root = tempfile::tempdir().unwrap();
+
+    mount_resync(
+        &server,
+        MockResponse::error(503, "backendError", "Backend Error"),
+    );
+
+    download(&server, root.path(), true).await;
+    let (results, events) = download(&server, root.path(), true).await;
+
+    assert!(matches!(
+        &results["ana@school.edu"].errors[..],
+        [SubmissionError::DownloadFailed(_)]
+    ));
+    assert!(
+        !events
+            .iter()
+            .any(|e| matches!(e, DownloadEvent::Resubmitted(_)))
+    );
+}
+
+struct FolderExport {
+    files: HashMap<String, Vec<u8>>,
+}
+
+impl SubmissionSource for FolderExport {
+    async fn list_offerings(&self) -> SourceResult<Vec<Offering>> {
+        Ok(vec![Offering {
+            id: "export".to_string(),
+            name: "Exported course".to_string(),
+        }])
+    }
+
+    async fn list_assignments(&self, _offering_id: &str) -> SourceResult<Vec<Assignment>> {
+        Ok(vec![Assignment {
+            id: "lista1".to_string(),
+            title: "Lista 1".to_string(),
+            max_points: None,
+        }])
+    }
+
+    async fn list_students(&self, _offering_id: &str) -> SourceResult<Vec<Student>> {
+        Ok(self
+            .files
+            .keys()
+            .map(|email| Student {
+                id: email.clone(),
+                email: email.clone(),
+                name: email.clone(),
+            })
+            .collect())
+    }
+
+    async fn list_submissions(
+        &self,
+        _offering_id: &str,
+        _assignment_id: &str,
+    ) -> SourceResult<Vec<Submission>> {
+        Ok(self
+            .files
+            .keys()
+            .map(|email| Submission {
+                id: email.clone(),
+                user_id: email.clone(),
+                turned_in: true,
+                late: email.starts_with("late"),
+                attachments: vec![SourceAttachment {
+                    id: email.clone(),
+                    title: format!("{email}.zip"),
+                }],
+            })
+            .collect())
+    }
+
+    async fn fetch_attachment(&self, attachment: &SourceAttachment) -> SourceResult<Vec<u8>> {
+        Ok(self.files[&attachment.id].clone())
+    }
+}
+
+#[tokio::test]
+async fn downloads_from_any_submission_source() {
+    let root = tempfile::tempdir().unwrap();
+    let source = FolderExport {
+        files: HashMap::from([
+            ("ana@school.edu".to_string(), solution_zip()),
+            ("late@school.edu".to_string(), solution_zip()),
+        ]),
+    };
+
+    let options = DownloadOptions {
+        root: root.path().to_path_buf(),
+        ..DownloadOptions::default()
+    };
+
+    let (tx, _rx) = mpsc::channel(100);
+    let mut results = HashMap::new();
+
+    download_submissions(
+        Arc::new(source),
+        "export",
+        "lista1",
+        &options,
+        &mut results,
+        tx,
+    )
+    .await
+    .unwrap();
+
+    assert!(results["ana@school.edu"].errors.is_empty());
+    assert!(matches!(
+        results["late@school.edu"].errors[..],
+        [SubmissionError::Late]
+    ));
+    assert!(
+        root.path()
+            .join("export/lista1/ana@school.edu/q1_ana@school.edu.c")
+            .is_file()
+    );
+}
+
+#[tokio::test]
+async fn local_folders_are_a_submission_source() {
+    let export = tempfile::tempdir().unwrap();
+    let root = tempfile::tempdir().unwrap();
+    std::fs::write(export.path().join("ana@school.edu.zip"), solution_zip()).unwrap();
+
+    let roster = HashMap::from([(
+        "bruno@school.edu".to_string(),
+        Student {
+            id: "bruno@school.edu".to_string(),
+            email: "bruno@school.edu".to_string(),
+            name: "Bruno".to_string(),
+        },
+    )]);
+    let folder = LocalFolder::new(export.path().to_path_buf(), Some(roster));
+
+    let options = DownloadOptions {
+        root: root.path().to_path_buf(),
+        ..DownloadOptions::default()
+    };
+
+    let (tx, _rx) = mpsc::channel(100);
+    let mut results = HashMap::new();
+
+    download_submissions(
+        Arc::new(folder),
+        LOCAL_ID,
+        LOCAL_ID,
+        &options,
+        &mut results,
+        tx,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(results["ana@school.edu"].solved, 2);
+    assert!(results["ana@school.edu"].errors.is_empty());
+    assert!(matches!(
+        results["bruno@school.edu"].errors[..],
+        [SubmissionError::NoSubmission]
+    ));
+    assert!(
+        root.path()
+            .join("local/local/ana@school.edu/q1_ana@school.edu.c")
+            .is_file()
+    );
+}
@@ -0,0 +1,276 @@
+use std::{collections::HashMap, sync::Arc};
+
+use app::grade_publisher::{GradeChange, GradeMode, PublishEvent, plan_grades, publish_grades};
+use classroom_mock::{
+    MockResponse, MockServer,
+    fixtures::{page, student, submission},
+};
+use reporter::scoring::ScoreBreakdown;
+use serde_json::{Value, json};
+use tokio::sync::mpsc;
+
+const WORK: &str = "/v1/courses/c1/courseWork/w1";
+const STUDENTS: &str = "/v1/courses/c1/students";
+const SUBMISSIONS: &str = "/v1/courses/c1/courseWork/w1/studentSubmissions";
+
+fn graded(
+    id: &str,
+    user_id: &str,
+    state: &str,
+    draft: Option<f64>,
+    assigned: Option<f64>,
+) -> Value {
+    let mut submission = submission(id, user_id, state, &[]);
+
+    if let Some(draft) = draft {
+        submission["draftGrade"] = json!(draft);
+    }
+
+    if let Some(assigned) = assigned {
+        submission["assignedGrade"] = json!(assigned);
+    }
+
+    submission
+}
+
+fn mount_course(server: &MockServer) {
+    server.mount(
+        "GET",
+        WORK,
+        MockResponse::json(&json!({ "id": "w1", "title": "Lab 3", "maxPoints": 10.0 })),
+    );
+    server.mount(
+        "GET",
+        STUDENTS,
+        MockResponse::json(&page(
+            "students",
+            vec![
+                student("u1", "ana@school.edu", "Ana"),
+                student("u2", "bruno@school.edu", "Bruno"),
+                student("u3", "carla@school.edu", "Carla"),
+                student("u4", "davi@school.edu", "Davi"),
+            ],
+            None,
+        )),
+    );
+    server.mount(
+        "GET",
+        SUBMISSIONS,
+        MockResponse::json(&page(
+            "studentSubmissions",
+            vec![
+                graded("s1", "u1", "TURNED_IN", Some(8.0), Some(7.0)),
+                graded("s2", "u2", "TURNED_IN", None, None),
+                graded("s3", "u3", "RETURNED", Some(9.0), Some(10.0)),
+                graded("s4", "u4", "TURNED_IN", None, None),
+            ],
+            None,
+        )),
+    );
+
+    for (id, user_id) in [("s1", "u1"), ("s2", "u2"), ("s3", "u3")] {
+        server.mount(
+            "PATCH",
+            &format!("{SUBMISSIONS}/{id}"),
+            MockResponse::json(&submission(id, user_id, "TURNED_IN", &[])),
+        );
+        server.mount(
+            "POST",
+            &format!("{SUBMISSIONS}/{id}:return"),
+            MockResponse::json(&json!({})),
+        );
+    }
+}
+
+fn breakdown(score: f32) -> ScoreBreakdown {
+    ScoreBreakdown {
+        solved: 1,
+        expected: 1,
+        completion: 1.0,
+        tests: None,
+        penalties: Vec::new(),
+        score,
+    }
+}
+
+fn scores() -> HashMap<String, ScoreBreakdown> {
+    HashMap::from([
+        ("ana@school.edu".to_string(), breakdown(0.8)),
+        ("bruno@school.edu".to_string(), breakdown(0.5)),
+        ("carla@school.edu".to_string(), breakdown(1.0)),
+    ])
+}
+
+async fn plan(server: &MockServer) -> Vec<GradeChange> {
+    plan_grades(&server.api(), "c1", "w1", &scores())
+        .await
+        .unwrap()
+}
+
+async fn publish(
+    server: &MockServer,
+    changes: &[GradeChange],
+    mode: GradeMode,
+    return_submissions: bool,
+) -> Vec<(String, String)> {
+    let (tx, mut rx) = mpsc::channel(100);
+
+    publish_grades(
+        Arc::new(server.api()),
+        "c1",
+        "w1",
+        changes,
+        mode,
+        return_submissions,
+        tx,
+    )
+    .await
+    .unwrap();
+
+    let mut failures = vec![];
+
+    while let Some(event) = rx.recv().await {
+        if let PublishEvent::Failed(email, message) = event {
+            failures.push((email, message));
+        }
+    }
+
+    failures
+}
+
+fn patches(server: &MockServer) -> Vec<(String, String, Value)> {
+    server
+        .requests()
+        .into_iter()
+        .filter(|r| r.method == "PATCH")
+        .map(|r| {
+            (
+                r.path.rsplit('/').next().unwrap().to_string(),
+                r.param("updateMask")
+                    .unwrap_or_default()
+                    .replace("%2C", ","),
+                serde_json::from_slice(&r.body).unwrap(),
+            )
+        })
+        .collect()
+}
+
+fn returned(server: &MockServer) -> Vec<String> {
+    server
+        .requests()
+        .into_iter()
+        .filter(|r| r.method == "POST" && r.path.ends_with(":return"))
+        .map(|r| r.path.rsplit('/').next().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn preview_compares_against_the_grade_being_written() {
+    let server = MockServer::start().await.unwrap();
+    mount_course(&server);
+
+    let changes = plan(&server).await;
+
+    let emails: Vec<&str> = changes.iter().map(|c| c.email.as_str()).collect();
+    assert_eq!(
+        emails,
+        ["ana@school.edu", "bruno@school.edu", "carla@school.edu"]
+    );
+
+    let grades: Vec<f64> = changes.iter().map(|c| c.new_grade).collect();
+    assert_eq!(grades, [8.0, 5.0, 10.0]);
+
+    let [ana, bruno, carla] = &changes[..] else {
+        panic!("expected three changes");
+    };
+
+    assert_eq!(ana.current(GradeMode::Draft), Some(8.0));
+    assert_eq!(ana.current(GradeMode::Assigned), Some(7.0));
+    assert!(!ana.is_changed(GradeMode::Draft));
+    assert!(ana.is_changed(GradeMode::Assigned));
+
+    assert_eq!(bruno.current(GradeMode::Draft), None);
+    assert!(bruno.is_changed(GradeMode::Draft));
+    assert!(bruno.is_changed(GradeMode::Assigned));
+
+    assert!(carla.is_changed(GradeMode::Draft));
+    assert!(!carla.is_changed(GradeMode::Assigned));
+
+    assert!(ana.returnable && bruno.returnable && !carla.returnable);
+}
+
+#[tokio::test]
+async fn draft_mode_only_writes_draft_grades() {
+    let server = MockServer::start().await.unwrap();
+    mount_course(&server);
+
+    let changes = plan(&server).await;
+    let failures = publish(&server, &changes, GradeMode::Draft, false).await;
+
+    assert!(failures.is_empty());
+    assert_eq!(
+        patches(&server),
+        [
+            (
+                "s2".to_string(),
+                "draftGrade".to_string(),
+                json!({ "draftGrade": 5.0 })
+            ),
+            (
+                "s3".to_string(),
+                "draftGrade".to_string(),
+                json!({ "draftGrade": 10.0 })
+            ),
+        ]
+    );
+    assert!(returned(&server).is_empty());
+}
+
+#[tokio::test]
+async fn assigned_mode_writes_both_grades() {
+    let server = MockServer::start().await.unwrap();
+    mount_course(&server);
+
+    let changes = plan(&server).await;
+    let failures = publish(&server, &changes, GradeMode::Assigned, false).await;
+
+    assert!(failures.is_empty());
+    assert_eq!(
+        patches(&server),
+        [
+            (
+                "s1".to_string(),
+                "draftGrade,assignedGrade".to_string(),
+                json!({ "draftGrade": 8.0, "assignedGrade": 8.0 })
+            ),
+            (
+                "s2".to_string(),
+                "draftGrade,assignedGrade".to_string(),
+                json!({ "draftGrade": 5.0, "assignedGrade": 5.0 })
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn failed_updates_are_reported_without_stopping_the_rest() {
+    let server = MockServer::start().await.unwrap();
+    server.mount(
+        "PATCH",
+        &format!("{SUBMISSIONS}/s2"),
+        MockResponse::error(403, "forbidden", "@ProjectPermissionDenied"),
+    );
+    mount_course(&server);
+
+    let changes = plan(&server).await;
+    let failures = publish(&server, &changes, GradeMode::Assigned, true).await;
+
+    let failed: Vec<&str> = failures.iter().map(|(email, _)| email.as_str()).collect();
+    assert_eq!(failed, ["bruno@school.edu"]);
+    assert!(failures[0].1.contains("ProjectPermissionDenied"));
+
+    let patched: Vec<String> = patches(&server).into_iter().map(|(id, ..)| id).collect();
+    assert_eq!(patched, ["s1", "s2"]);
+
+    assert_eq!(returned(&server), ["s1:return"]);
+}
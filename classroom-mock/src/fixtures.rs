@@ -0,0 +1,85 @@
+use std::io::{Cursor, Write};
+
+use serde_json::{Value, json};
+use zip::{ZipWriter, write::SimpleFileOptions};
+
+#[must_use]
+pub fn student(user_id: &str, email: &str, name: &str) -> Value {
+    json!({
+        "userId": user_id,
+        "profile": {
+            "id": user_id,
+            "emailAddress": email,
+            "name": { "fullName": name },
+        },
+    })
+}
+
+#[must_use]
+pub fn submission(id: &str, user_id: &str, state: &str, file_ids: &[&str]) -> Value {
+    let mut submission = json!({
+        "id": id,
+        "userId": user_id,
+        "state": state,
+        "assignmentSubmission": {},
+    });
+
+    if !file_ids.is_empty() {
+        submission["assignmentSubmission"]["attachments"] = file_ids
+            .iter()
+            .map(|id| {
+                json!({
+                    "driveFile": {
+                        "id": id,
+                        "title": format!("{id}.zip"),
+                        "alternateLink": format!("https://drive.google.com/file/d/{id}"),
+                    }
+                })
+            })
+            .collect();
+    }
+
+    submission
+}
+
+#[must_use]
+pub fn page(field: &str, items: Vec<Value>, next_page_token: Option<&str>) -> Value {
+    let mut page = json!({ field: items });
+
+    if let Some(token) = next_page_token {
+        page["nextPageToken"] = json!(token);
+    }
+
+    page
+}
+
+#[must_use]
+pub fn drive_metadata(file_id: &str, md5_checksum: &str) -> Value {
+    json!({
+        "id": file_id,
+        "modifiedTime": "2026-03-01T12:00:00Z",
+        "md5Checksum": md5_checksum,
+    })
+}
+
+#[must_use]
+pub fn drive_document_metadata(file_id: &str, modified_time: &str) -> Value {
+    json!({
+        "id": file_id,
+        "modifiedTime": modified_time,
+    })
+}
+
+#[must_use]
+pub fn zip(files: &[(&str, &str)]) -> Vec<u8> {
+    let mut writer = ZipWriter::new(Cursor::new(vec![]));
+
+    for (name, content) in files {
+        writer
+            .start_file(*name, SimpleFileOptions::default())
+            .expect("zip entry");
+        writer.write_all(content.as_bytes()).expect("zip content");
+    }
+
+    writer.finish().expect("zip archive").into_inner()
+}
@@ -0,0 +1,308 @@
+pub mod fixtures;
+
+use std::{
+    collections::{HashMap, VecDeque},
+    io,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use classroom::{
+    api::{ApiOptions, ClassroomApi},
+    client::ClassroomClient,
+};
+use serde_json::{Value, json};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    task::JoinHandle,
+};
+
+pub const MOCK_TOKEN: &str = "mock-token";
+
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    pub truncated_at: Option<usize>,
+}
+
+impl MockResponse {
+    #[must_use]
+    pub fn json(value: &Value) -> Self {
+        Self {
+            status: 200,
+            headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+            body: value.to_string().into_bytes(),
+            truncated_at: None,
+        }
+    }
+
+    #[must_use]
+    pub fn bytes(body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status: 200,
+            headers: vec![(
+                "Content-Type".to_string(),
+                "application/octet-stream".to_string(),
+            )],
+            body: body.into(),
+            truncated_at: None,
+        }
+    }
+
+    #[must_use]
+    pub fn error(status: u16, reason: &str, message: &str) -> Self {
+        Self {
+            status,
+            ..Self::json(&json!({
+                "error": {
+                    "code": status,
+                    "message": message,
+                    "errors": [{ "reason": reason, "message": message }],
+                }
+            }))
+        }
+    }
+
+    #[must_use]
+    pub fn truncated(mut self, at: usize) -> Self {
+        self.truncated_at = Some(at);
+        self
+    }
+
+    #[must_use]
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub query: Vec<(String, String)>,
+    pub authorization: Option<String>,
+    pub body: Vec<u8>,
+}
+
+impl RecordedRequest {
+    #[must_use]
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+type Routes = Arc<Mutex<HashMap<String, VecDeque<MockResponse>>>>;
+
+pub struct MockServer {
+    addr: SocketAddr,
+    routes: Routes,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+    handle: JoinHandle<()>,
+}
+
+impl MockServer {
+    pub async fn start() -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let routes: Routes = Arc::default();
+        let requests: Arc<Mutex<Vec<RecordedRequest>>> = Arc::default();
+
+        let handle = tokio::spawn({
+            let routes = Arc::clone(&routes);
+            let requests = Arc::clone(&requests);
+
+            async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    let routes = Arc::clone(&routes);
+                    let requests = Arc::clone(&requests);
+
+                    tokio::spawn(async move {
+                        _ = serve(stream, routes, requests).await;
+                    });
+                }
+            }
+        });
+
+        Ok(Self {
+            addr,
+            routes,
+            requests,
+            handle,
+        })
+    }
+
+    #[must_use]
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    pub fn mount(&self, method: &str, path: &str, response: MockResponse) {
+        self.routes
+            .lock()
+            .unwrap()
+            .entry(format!("{method} {path}"))
+            .or_default()
+            .push_back(response);
+    }
+
+    #[must_use]
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    #[must_use]
+    pub fn requests_to(&self, path: &str) -> Vec<RecordedRequest> {
+        self.requests()
+            .into_iter()
+            .filter(|r| r.path == path)
+            .collect()
+    }
+
+    #[must_use]
+    pub fn api_options(&self) -> ApiOptions {
+        ApiOptions {
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(10),
+            classroom_url: format!("{}/v1", self.url()),
+            drive_url: format!("{}/drive/v3", self.url()),
+            ..ApiOptions::default()
+        }
+    }
+
+    #[must_use]
+    pub fn api(&self) -> ClassroomApi {
+        ClassroomApi::with_options(ClassroomClient::with_token(MOCK_TOKEN), self.api_options())
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+const ROUTE_PARAMS: &[&str] = &["alt", "pageToken"];
+
+fn route_key(method: &str, path: &str, query: &[(String, String)]) -> String {
+    let params: Vec<String> = query
+        .iter()
+        .filter(|(k, _)| ROUTE_PARAMS.contains(&k.as_str()))
+        .map(|(k, v)| format!("{k}={v}"))
+        .collect();
+
+    if params.is_empty() {
+        format!("{method} {path}")
+    } else {
+        format!("{method} {path}?{}", params.join("&"))
+    }
+}
+
+fn respond(routes: &Routes, key: &str) -> MockResponse {
+    let mut routes = routes.lock().unwrap();
+
+    match routes.get_mut(key) {
+        Some(queue) if queue.len() > 1 => queue.pop_front().unwrap(),
+        Some(queue) if !queue.is_empty() => queue[0].clone(),
+        _ => MockResponse::error(404, "notFound", &format!("no mock for {key}")),
+    }
+}
+
+async fn serve(
+    mut stream: TcpStream,
+    routes: Routes,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+) -> io::Result<()> {
+    let mut buf = vec![];
+    let mut chunk = [0; 4096];
+
+    let head_end = loop {
+        let n = stream.read(&mut chunk).await?;
+
+        if n == 0 {
+            return Ok(());
+        }
+
+        buf.extend_from_slice(&chunk[..n]);
+
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buf[..head_end]).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_string();
+    let target = request_line.next().unwrap_or_default().to_string();
+
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|l| l.split_once(':'))
+        .map(|(k, v)| (k.trim().to_lowercase(), v.trim().to_string()))
+        .collect();
+
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.clone())
+    };
+
+    let content_length: usize = header("content-length")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+
+    while buf.len() < head_end + content_length {
+        let n = stream.read(&mut chunk).await?;
+
+        if n == 0 {
+            break;
+        }
+
+        buf.extend_from_slice(&chunk[..n]);
+    }
+
+    let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+    let query: Vec<(String, String)> = query
+        .split('&')
+        .filter(|p| !p.is_empty())
+        .map(|p| {
+            let (k, v) = p.split_once('=').unwrap_or((p, ""));
+            (k.to_string(), v.to_string())
+        })
+        .collect();
+
+    let response = respond(&routes, &route_key(&method, path, &query));
+
+    requests.lock().unwrap().push(RecordedRequest {
+        method,
+        path: path.to_string(),
+        query,
+        authorization: header("authorization"),
+        body: buf[head_end..].to_vec(),
+    });
+
+    let mut out = format!(
+        "HTTP/1.1 {} \r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        response.body.len()
+    );
+
+    for (name, value) in &response.headers {
+        out.push_str(&format!("{name}: {value}\r\n"));
+    }
+
+    out.push_str("\r\n");
+
+    stream.write_all(out.as_bytes()).await?;
+    let sent = response.truncated_at.unwrap_or(response.body.len());
+    stream.write_all(&response.body[..sent]).await?;
+    stream.shutdown().await
+}
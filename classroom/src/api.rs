@@ -1,170 +1,335 @@
-use reqwest::{Client, Response};
+use std::{
+    hash::{BuildHasher, RandomState},
+    time::Duration,
+};
+
+use reqwest::{Client, RequestBuilder, header::CONTENT_TYPE};
+use serde::de::DeserializeOwned;
 
 use crate::client::ClassroomClient;
-use crate::models::{Course, CourseWorks, Courses, Student, StudentSubmissions, Students};
+use crate::error::ClassroomError;
+use crate::limiter::RateLimiter;
+use crate::models::{
+    Course, CourseWork, CourseWorks, Courses, DriveFileMetadata, GradePatch, Student,
+    StudentSubmission, StudentSubmissions, Students,
+};
+use crate::pagination::Pages;
+
+pub(crate) fn decode<T: DeserializeOwned>(body: &[u8], what: &str) -> Result<T, ClassroomError> {
+    serde_json::from_slice(body).map_err(|e| ClassroomError::Decode(what.to_string(), e))
+}
+
+#[derive(Debug, Clone)]
+pub struct ApiOptions {
+    pub max_concurrent: usize,
+    pub requests_per_second: Option<f64>,
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub classroom_url: String,
+    pub drive_url: String,
+}
+
+impl Default for ApiOptions {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 8,
+            requests_per_second: None,
+            max_retries: 5,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(32),
+            classroom_url: "https://classroom.googleapis.com/v1".to_string(),
+            drive_url: "https://www.googleapis.com/drive/v3".to_string(),
+        }
+    }
+}
+
+impl ApiOptions {
+    fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+
+        let jitter = RandomState::new().hash_one(attempt) % 1000;
+
+        delay.mul_f64(0.5 + jitter as f64 / 2000.0)
+    }
+}
 
 pub struct ClassroomApi {
     http_client: Client,
     classroom_client: ClassroomClient,
+    limiter: RateLimiter,
+    options: ApiOptions,
 }
 
 impl ClassroomApi {
     #[must_use]
     pub fn new(classroom_client: ClassroomClient) -> Self {
+        Self::with_options(classroom_client, ApiOptions::default())
+    }
+
+    #[must_use]
+    pub fn with_options(classroom_client: ClassroomClient, options: ApiOptions) -> Self {
         Self {
             http_client: Client::new(),
             classroom_client,
+            limiter: RateLimiter::new(options.max_concurrent, options.requests_per_second),
+            options,
         }
     }
 
-    pub async fn list_courses(&self) -> Result<Courses, Box<dyn std::error::Error + Send + Sync>> {
-        let token = self.classroom_client.token().ok_or("Token not found")?;
+    pub(crate) async fn send(
+        &self,
+        idempotent: bool,
+        build: impl Fn(&Client) -> RequestBuilder,
+    ) -> Result<Vec<u8>, ClassroomError> {
+        let mut attempt = 0;
 
-        let resp = self
-            .http_client
-            .get("https://classroom.googleapis.com/v1/courses")
-            .bearer_auth(token)
-            .send()
-            .await?;
+        loop {
+            let result = {
+                let _permit = self.limiter.acquire().await;
+
+                match build(&self.http_client).send().await {
+                    Ok(resp) => match ClassroomError::check(resp).await {
+                        Ok(resp) => resp.bytes().await.map(|b| b.to_vec()).map_err(Into::into),
+                        Err(e) => Err(e),
+                    },
+                    Err(e) => Err(e.into()),
+                }
+            };
+
+            match result {
+                Err(e) if idempotent && attempt < self.options.max_retries && e.is_retryable() => {
+                    let delay = e.retry_after().map_or_else(
+                        || self.options.backoff(attempt),
+                        |d| d.min(self.options.max_delay),
+                    );
+
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
 
-        let courses: Courses = resp.json().await?;
+    pub(crate) fn token(&self) -> Result<&str, ClassroomError> {
+        self.classroom_client
+            .token()
+            .ok_or(ClassroomError::MissingToken)
+    }
+
+    pub fn course_pages(&self) -> Pages<'_, Courses> {
+        let classroom = &self.options.classroom_url;
 
-        Ok(courses)
+        Pages::new(self, format!("{classroom}/courses"), "courses")
     }
 
-    pub async fn list_course(
-        &self,
-        course_id: &str,
-    ) -> Result<Course, Box<dyn std::error::Error + Send + Sync>> {
-        let token = self.classroom_client.token().ok_or("Token not found")?;
-
-        let resp = self
-            .http_client
-            .get(format!(
-                "https://classroom.googleapis.com/v1/courses/{course_id}"
-            ))
-            .bearer_auth(token)
-            .send()
+    pub async fn list_courses(&self) -> Result<Courses, ClassroomError> {
+        self.course_pages().collect().await
+    }
+
+    pub async fn list_course(&self, course_id: &str) -> Result<Course, ClassroomError> {
+        let token = self.token()?;
+        let classroom = &self.options.classroom_url;
+
+        let body = self
+            .send(true, |c| {
+                c.get(format!("{classroom}/courses/{course_id}"))
+                    .bearer_auth(token)
+            })
             .await?;
 
-        let course: Course = resp.json().await?;
+        let course: Course = decode(&body, "course")?;
 
         Ok(course)
     }
 
-    pub async fn list_course_works(
+    pub fn course_work_pages(&self, course_id: &str) -> Pages<'_, CourseWorks> {
+        let classroom = &self.options.classroom_url;
+
+        Pages::new(
+            self,
+            format!("{classroom}/courses/{course_id}/courseWork"),
+            "course works",
+        )
+    }
+
+    pub async fn list_course_works(&self, course_id: &str) -> Result<CourseWorks, ClassroomError> {
+        self.course_work_pages(course_id).collect().await
+    }
+
+    pub async fn get_course_work(
         &self,
         course_id: &str,
-    ) -> Result<CourseWorks, Box<dyn std::error::Error + Send + Sync>> {
-        let token = self.classroom_client.token().ok_or("Token not found")?;
-
-        let resp = self
-            .http_client
-            .get(format!(
-                "https://classroom.googleapis.com/v1/courses/{course_id}/courseWork"
-            ))
-            .bearer_auth(token)
-            .send()
+        course_work_id: &str,
+    ) -> Result<CourseWork, ClassroomError> {
+        let token = self.token()?;
+        let classroom = &self.options.classroom_url;
+
+        let body = self
+            .send(true, |c| {
+                c.get(format!(
+                    "{classroom}/courses/{course_id}/courseWork/{course_work_id}"
+                ))
+                .bearer_auth(token)
+            })
             .await?;
 
-        let works: CourseWorks = resp.json().await?;
+        let work: CourseWork = decode(&body, "course work")?;
 
-        Ok(works)
+        Ok(work)
     }
 
     pub async fn get_student(
         &self,
         course_id: &str,
         user_id: &str,
-    ) -> Result<Student, Box<dyn std::error::Error + Send + Sync>> {
-        let token = self.classroom_client.token().ok_or("Token not found")?;
-
-        let resp = self
-            .http_client
-            .get(format!(
-                "https://classroom.googleapis.com/v1/courses/{course_id}/students/{user_id}"
-            ))
-            .bearer_auth(token)
-            .send()
+    ) -> Result<Student, ClassroomError> {
+        let token = self.token()?;
+        let classroom = &self.options.classroom_url;
+
+        let body = self
+            .send(true, |c| {
+                c.get(format!(
+                    "{classroom}/courses/{course_id}/students/{user_id}"
+                ))
+                .bearer_auth(token)
+            })
             .await?;
 
-        let student: Student = resp.json().await?;
+        let student: Student = decode(&body, "student")?;
 
         Ok(student)
     }
 
-    pub async fn list_students(
-        &self,
-        course_id: &str,
-    ) -> Result<Students, Box<dyn std::error::Error + Send + Sync>> {
-        let token = self.classroom_client.token().ok_or("Token not found")?;
+    pub fn student_pages(&self, course_id: &str) -> Pages<'_, Students> {
+        let classroom = &self.options.classroom_url;
 
-        let mut students = Students {
-            students: vec![],
-            next_page_token: None,
-        };
-
-        let mut next_page_token = None;
+        Pages::new(
+            self,
+            format!("{classroom}/courses/{course_id}/students"),
+            "students",
+        )
+    }
 
-        loop {
-            let mut url = format!(
-                "https://classroom.googleapis.com/v1/courses/{course_id}/students?pageSize=100"
-            );
+    pub async fn list_students(&self, course_id: &str) -> Result<Students, ClassroomError> {
+        self.student_pages(course_id).collect().await
+    }
 
-            if let Some(ref ptk) = next_page_token {
-                url.push_str(&format!("&pageToken={ptk}"));
-            }
+    pub fn student_submission_pages(
+        &self,
+        course_id: &str,
+        course_work_id: &str,
+    ) -> Pages<'_, StudentSubmissions> {
+        let classroom = &self.options.classroom_url;
+
+        Pages::new(
+            self,
+            format!(
+                "{classroom}/courses/{course_id}/courseWork/{course_work_id}/studentSubmissions"
+            ),
+            "student submissions",
+        )
+    }
 
-            let resp = self.http_client.get(url).bearer_auth(token).send().await?;
+    pub async fn get_student_submissions(
+        &self,
+        course_id: &str,
+        course_work_id: &str,
+    ) -> Result<StudentSubmissions, ClassroomError> {
+        self.student_submission_pages(course_id, course_work_id)
+            .collect()
+            .await
+    }
 
-            let stds: Students = resp.json().await?;
+    pub async fn download_student_submission(
+        &self,
+        file_id: &str,
+    ) -> Result<Vec<u8>, ClassroomError> {
+        let token = self.token()?;
+        let drive = &self.options.drive_url;
+
+        let body = self
+            .send(true, |c| {
+                c.get(format!("{drive}/files/{file_id}?alt=media"))
+                    .bearer_auth(token)
+            })
+            .await?;
 
-            students.students.extend(stds.students);
+        Ok(body)
+    }
 
-            next_page_token = stds.next_page_token;
+    pub async fn get_drive_file_metadata(
+        &self,
+        file_id: &str,
+    ) -> Result<DriveFileMetadata, ClassroomError> {
+        let token = self.token()?;
+        let drive = &self.options.drive_url;
+
+        let body = self
+            .send(true, |c| {
+                c.get(format!(
+                    "{drive}/files/{file_id}?fields=id,modifiedTime,md5Checksum"
+                ))
+                .bearer_auth(token)
+            })
+            .await?;
 
-            if next_page_token.is_none() {
-                break;
-            }
-        }
+        let metadata: DriveFileMetadata = decode(&body, "Drive file metadata")?;
 
-        Ok(students)
+        Ok(metadata)
     }
 
-    pub async fn get_student_submissions(
+    pub async fn patch_student_submission_grade(
         &self,
         course_id: &str,
         course_work_id: &str,
-    ) -> Result<StudentSubmissions, Box<dyn std::error::Error + Send + Sync>> {
-        let token = self.classroom_client.token().ok_or("Token not found")?;
-
-        let resp = self
-            .http_client
-            .get(format!("https://classroom.googleapis.com/v1/courses/{course_id}/courseWork/{course_work_id}/studentSubmissions"))
-            .bearer_auth(token)
-            .send()
+        submission_id: &str,
+        patch: &GradePatch,
+    ) -> Result<StudentSubmission, ClassroomError> {
+        let token = self.token()?;
+        let classroom = &self.options.classroom_url;
+        let url = format!(
+            "{classroom}/courses/{course_id}/courseWork/{course_work_id}/studentSubmissions/{submission_id}"
+        );
+
+        let body = self
+            .send(true, |c| {
+                c.patch(&url)
+                    .query(&[("updateMask", patch.update_mask())])
+                    .bearer_auth(token)
+                    .json(patch)
+            })
             .await?;
 
-        let submissions: StudentSubmissions = resp.json().await?;
+        let submission: StudentSubmission = decode(&body, "student submission")?;
 
-        Ok(submissions)
+        Ok(submission)
     }
 
-    pub async fn download_student_submission(
+    pub async fn return_student_submission(
         &self,
-        file_id: &str,
-    ) -> Result<Response, Box<dyn std::error::Error + Send + Sync>> {
-        let token = self.classroom_client.token().ok_or("Token not found")?;
-
-        let resp = self
-            .http_client
-            .get(format!(
-                "https://www.googleapis.com/drive/v3/files/{file_id}?alt=media"
-            ))
-            .bearer_auth(token)
-            .send()
-            .await?;
-
-        Ok(resp)
+        course_id: &str,
+        course_work_id: &str,
+        submission_id: &str,
+    ) -> Result<(), ClassroomError> {
+        let token = self.token()?;
+        let classroom = &self.options.classroom_url;
+        let url = format!(
+            "{classroom}/courses/{course_id}/courseWork/{course_work_id}/studentSubmissions/{submission_id}:return"
+        );
+
+        self.send(false, |c| {
+            c.post(&url)
+                .bearer_auth(token)
+                .header(CONTENT_TYPE, "application/json")
+                .body("{}")
+        })
+        .await?;
+
+        Ok(())
     }
 }
@@ -25,6 +25,22 @@ impl InstalledFlowDelegate for InstalledFlowBrowserDelegate {
     }
 }
 
+pub const READ_SCOPES: &[&str] = &[
+    "https://www.googleapis.com/auth/classroom.courses.readonly",
+    "https://www.googleapis.com/auth/classroom.coursework.students.readonly",
+    "https://www.googleapis.com/auth/classroom.profile.emails",
+    "https://www.googleapis.com/auth/classroom.rosters.readonly",
+    "https://www.googleapis.com/auth/drive.readonly",
+];
+
+pub const GRADE_SCOPES: &[&str] = &[
+    "https://www.googleapis.com/auth/classroom.courses.readonly",
+    "https://www.googleapis.com/auth/classroom.coursework.students",
+    "https://www.googleapis.com/auth/classroom.profile.emails",
+    "https://www.googleapis.com/auth/classroom.rosters.readonly",
+    "https://www.googleapis.com/auth/drive.readonly",
+];
+
 #[derive(Default)]
 pub struct ClassroomClient {
     access_token: Option<String>,
@@ -36,9 +52,24 @@ impl ClassroomClient {
         Self::default()
     }
 
+    #[must_use]
+    pub fn with_token(access_token: impl Into<String>) -> Self {
+        Self {
+            access_token: Some(access_token.into()),
+        }
+    }
+
     pub async fn auth(
         &mut self,
         credentials_path: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.auth_with_scopes(credentials_path, READ_SCOPES).await
+    }
+
+    pub async fn auth_with_scopes(
+        &mut self,
+        credentials_path: &str,
+        scopes: &[&str],
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let secret = read_application_secret(credentials_path).await?;
 
@@ -49,14 +80,6 @@ impl ClassroomClient {
                 .build()
                 .await?;
 
-        let scopes = &[
-            "https://www.googleapis.com/auth/classroom.courses.readonly",
-            "https://www.googleapis.com/auth/classroom.coursework.students.readonly",
-            "https://www.googleapis.com/auth/classroom.profile.emails",
-            "https://www.googleapis.com/auth/classroom.rosters.readonly",
-            "https://www.googleapis.com/auth/drive.readonly",
-        ];
-
         let token = auth.token(scopes).await?;
         self.access_token = Some(token.token().ok_or("No token")?.to_owned());
 
@@ -0,0 +1,190 @@
+use std::{
+    fmt,
+    time::{Duration, SystemTime},
+};
+
+use reqwest::{Response, StatusCode, header::RETRY_AFTER};
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct GoogleErrorReason {
+    #[serde(default)]
+    pub reason: String,
+    #[serde(default)]
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct GoogleError {
+    #[serde(default)]
+    pub code: u16,
+    #[serde(default)]
+    pub message: String,
+    pub status: Option<String>,
+    #[serde(default)]
+    pub errors: Vec<GoogleErrorReason>,
+}
+
+#[derive(Deserialize)]
+struct GoogleErrorBody {
+    error: GoogleError,
+}
+
+impl GoogleError {
+    fn from_body(status: StatusCode, body: &str) -> Self {
+        serde_json::from_str::<GoogleErrorBody>(body)
+            .map(|b| b.error)
+            .unwrap_or_else(|_| GoogleError {
+                code: status.as_u16(),
+                message: body.trim().to_string(),
+                ..GoogleError::default()
+            })
+    }
+
+    fn has_reason(&self, reasons: &[&str]) -> bool {
+        self.errors
+            .iter()
+            .any(|e| reasons.contains(&e.reason.as_str()))
+    }
+}
+
+impl fmt::Display for GoogleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.status {
+            Some(status) => write!(f, "{status}: {}", self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    if let Ok(secs) = value.trim().parse() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let at = httpdate::parse_http_date(value.trim()).ok()?;
+
+    Some(
+        at.duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+#[derive(Debug)]
+pub enum ClassroomError {
+    MissingToken,
+    Unauthorized(GoogleError),
+    PermissionDenied(GoogleError),
+    NotFound(GoogleError),
+    RateLimited(GoogleError, Option<Duration>),
+    Api(StatusCode, GoogleError),
+    Http(reqwest::Error),
+    Decode(String, serde_json::Error),
+}
+
+impl ClassroomError {
+    pub(crate) async fn check(resp: Response) -> Result<Response, ClassroomError> {
+        let status = resp.status();
+
+        if status.is_success() {
+            return Ok(resp);
+        }
+
+        let retry_after = resp
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(parse_retry_after);
+
+        let body = resp.text().await.unwrap_or_default();
+        let details = GoogleError::from_body(status, &body);
+
+        Err(match status {
+            StatusCode::UNAUTHORIZED => ClassroomError::Unauthorized(details),
+            StatusCode::TOO_MANY_REQUESTS => ClassroomError::RateLimited(details, retry_after),
+            StatusCode::FORBIDDEN
+                if details.has_reason(&["rateLimitExceeded", "userRateLimitExceeded"]) =>
+            {
+                ClassroomError::RateLimited(details, retry_after)
+            }
+            StatusCode::FORBIDDEN => ClassroomError::PermissionDenied(details),
+            StatusCode::NOT_FOUND => ClassroomError::NotFound(details),
+            _ => ClassroomError::Api(status, details),
+        })
+    }
+
+    #[must_use]
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ClassroomError::RateLimited(..) => true,
+            ClassroomError::Api(status, _) => status.is_server_error(),
+            ClassroomError::Http(e) => {
+                e.is_timeout() || e.is_connect() || e.is_body() || e.is_decode()
+            }
+            _ => false,
+        }
+    }
+
+    #[must_use]
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            ClassroomError::RateLimited(_, retry_after) => *retry_after,
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ClassroomError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClassroomError::MissingToken => {
+                write!(f, "not authenticated with Google Classroom")
+            }
+            ClassroomError::Unauthorized(e) => {
+                write!(f, "authentication failed, try signing in again ({e})")
+            }
+            ClassroomError::PermissionDenied(e) => write!(f, "permission denied ({e})"),
+            ClassroomError::NotFound(e) => write!(f, "not found ({e})"),
+            ClassroomError::RateLimited(e, _) => write!(f, "rate limit exceeded ({e})"),
+            ClassroomError::Api(status, e) => write!(f, "request failed with {status} ({e})"),
+            ClassroomError::Http(e) => write!(f, "request failed: {e}"),
+            ClassroomError::Decode(what, e) => write!(f, "could not decode {what}: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ClassroomError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ClassroomError::Http(e) => Some(e),
+            ClassroomError::Decode(_, e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for ClassroomError {
+    fn from(e: reqwest::Error) -> Self {
+        ClassroomError::Http(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_after_accepts_seconds_and_http_dates() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+
+        let later = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(90));
+        let delay = parse_retry_after(&later).unwrap();
+        assert!(delay > Duration::from_secs(80) && delay <= Duration::from_secs(90));
+
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+}
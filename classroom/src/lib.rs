@@ -1,3 +1,6 @@
 pub mod api;
 pub mod client;
+pub mod error;
+pub mod limiter;
 pub mod models;
+pub mod pagination;
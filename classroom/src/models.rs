@@ -1,8 +1,11 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
 pub struct Courses {
+    #[serde(default)]
     pub courses: Vec<Course>,
+    pub next_page_token: Option<String>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -19,6 +22,14 @@ pub struct DriveFile {
     pub drive_link: String,
 }
 
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DriveFileMetadata {
+    pub id: String,
+    pub modified_time: Option<String>,
+    pub md5_checksum: Option<String>,
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Attachment {
@@ -44,16 +55,47 @@ pub enum SubmissionState {
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct StudentSubmission {
+    pub id: String,
     pub user_id: String,
     pub late: Option<bool>,
     pub state: SubmissionState,
     pub assignment_submission: Option<AssignmentSubmission>,
+    pub draft_grade: Option<f64>,
+    pub assigned_grade: Option<f64>,
+}
+
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GradePatch {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub draft_grade: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assigned_grade: Option<f64>,
+}
+
+impl GradePatch {
+    #[must_use]
+    pub fn update_mask(&self) -> String {
+        let mut fields = vec![];
+
+        if self.draft_grade.is_some() {
+            fields.push("draftGrade");
+        }
+
+        if self.assigned_grade.is_some() {
+            fields.push("assignedGrade");
+        }
+
+        fields.join(",")
+    }
 }
 
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct StudentSubmissions {
+    #[serde(default)]
     pub student_submissions: Vec<StudentSubmission>,
+    pub next_page_token: Option<String>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -80,18 +122,23 @@ pub struct Student {
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Students {
+    #[serde(default)]
     pub students: Vec<Student>,
     pub next_page_token: Option<String>,
 }
 
 #[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
 pub struct CourseWork {
     pub id: String,
     pub title: String,
+    pub max_points: Option<f64>,
 }
 
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct CourseWorks {
+    #[serde(default)]
     pub course_work: Vec<CourseWork>,
+    pub next_page_token: Option<String>,
 }
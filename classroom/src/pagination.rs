@@ -0,0 +1,124 @@
+use std::marker::PhantomData;
+
+use futures_util::{Stream, TryStreamExt, stream};
+use serde::de::DeserializeOwned;
+
+use crate::api::ClassroomApi;
+use crate::error::ClassroomError;
+use crate::models::{CourseWorks, Courses, StudentSubmissions, Students};
+
+const PAGE_SIZE: &str = "100";
+
+pub trait Page: DeserializeOwned {
+    type Item;
+
+    fn into_parts(self) -> (Vec<Self::Item>, Option<String>);
+
+    fn from_items(items: Vec<Self::Item>) -> Self;
+}
+
+macro_rules! page {
+    ($page:ident, $field:ident, $item:ty) => {
+        impl Page for $page {
+            type Item = $item;
+
+            fn into_parts(self) -> (Vec<Self::Item>, Option<String>) {
+                (self.$field, self.next_page_token)
+            }
+
+            fn from_items(items: Vec<Self::Item>) -> Self {
+                Self {
+                    $field: items,
+                    next_page_token: None,
+                }
+            }
+        }
+    };
+}
+
+page!(Courses, courses, crate::models::Course);
+page!(CourseWorks, course_work, crate::models::CourseWork);
+page!(Students, students, crate::models::Student);
+page!(
+    StudentSubmissions,
+    student_submissions,
+    crate::models::StudentSubmission
+);
+
+pub struct Pages<'a, P> {
+    api: &'a ClassroomApi,
+    url: String,
+    what: &'static str,
+    next_page_token: Option<String>,
+    done: bool,
+    page: PhantomData<P>,
+}
+
+impl<'a, P: Page> Pages<'a, P> {
+    pub(crate) fn new(api: &'a ClassroomApi, url: String, what: &'static str) -> Self {
+        Self {
+            api,
+            url,
+            what,
+            next_page_token: None,
+            done: false,
+            page: PhantomData,
+        }
+    }
+
+    pub async fn next_page(&mut self) -> Result<Option<Vec<P::Item>>, ClassroomError> {
+        if self.done {
+            return Ok(None);
+        }
+
+        let token = self.api.token()?;
+        let page_token = self.next_page_token.take();
+
+        let body = self
+            .api
+            .send(true, |c| {
+                let req = c
+                    .get(&self.url)
+                    .query(&[("pageSize", PAGE_SIZE)])
+                    .bearer_auth(token);
+
+                match &page_token {
+                    Some(ptk) => req.query(&[("pageToken", ptk)]),
+                    None => req,
+                }
+            })
+            .await?;
+
+        let page: P = crate::api::decode(&body, self.what)?;
+        let (items, next_page_token) = page.into_parts();
+
+        self.done = next_page_token.is_none();
+        self.next_page_token = next_page_token;
+
+        Ok(Some(items))
+    }
+
+    pub async fn collect(mut self) -> Result<P, ClassroomError> {
+        let mut items = vec![];
+
+        while let Some(page) = self.next_page().await? {
+            items.extend(page);
+        }
+
+        Ok(P::from_items(items))
+    }
+
+    pub fn into_stream(self) -> impl Stream<Item = Result<P::Item, ClassroomError>> + 'a
+    where
+        P: 'a,
+    {
+        stream::try_unfold(self, |mut pages| async move {
+            let items = pages.next_page().await?;
+
+            Ok::<_, ClassroomError>(
+                items.map(|items| (stream::iter(items.into_iter().map(Ok)), pages)),
+            )
+        })
+        .try_flatten()
+    }
+}
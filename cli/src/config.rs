@@ -0,0 +1,547 @@
+use clap::{Args, Parser, Subcommand};
+use serde::Deserialize;
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use app::{
+    corpus::CorpusOptions,
+    grade_publisher::GradeMode,
+    judge::JudgeOptions,
+    similarity_checker::{SimilarityMethod, SimilarityOptions, UnknownQuestionPolicy},
+};
+use classroom::api::ApiOptions;
+use reporter::scoring::Rubric;
+use similarity::{Language, SimilarityConfig as ScoringConfig, WinnowingConfig};
+
+pub const DEFAULT_CONFIG: &str = "./subgrader.toml";
+pub const DEFAULT_CACHE: &str = "./.subgrader-cache";
+
+#[derive(Parser)]
+#[command(
+    name = "subgrader",
+    version,
+    about = "Automatic grading assistant for coding exercises"
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Download, analyze and report on an assignment
+    Run(RunArgs),
+    /// Analyze a local submissions folder without contacting Classroom
+    Analyze(AnalyzeArgs),
+}
+
+#[derive(Args, Default)]
+pub struct StepArgs {
+    /// TOML config file (defaults to ./subgrader.toml when present)
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+    /// Languages to extract and analyze (c, cpp, java, python), defaults to c
+    #[arg(long = "language", value_delimiter = ',')]
+    pub languages: Vec<Language>,
+    /// Similarity threshold (%), enables the similarity check
+    #[arg(long)]
+    pub threshold: Option<u32>,
+    /// Similarity method (combined or winnowing)
+    #[arg(long)]
+    pub method: Option<SimilarityMethod>,
+    /// Files without a question number (skip, same-group or compare-all)
+    #[arg(long)]
+    pub unknown: Option<UnknownQuestionPolicy>,
+    /// Starter code folder subtracted before scoring
+    #[arg(long)]
+    pub baseline: Option<PathBuf>,
+    /// Corpus folder with submissions archived from previous offerings
+    #[arg(long)]
+    pub corpus: Option<PathBuf>,
+    /// Offering this run belongs to in the corpus (defaults to the course ID)
+    #[arg(long)]
+    pub corpus_course: Option<String>,
+    /// Assignment label shared across offerings, required with the corpus
+    #[arg(long)]
+    pub corpus_assignment: Option<String>,
+    /// Save the analyzed submissions to the corpus
+    #[arg(long)]
+    pub archive: bool,
+    /// Folder where analyzed files and pair scores are cached between runs
+    #[arg(long)]
+    pub cache: Option<PathBuf>,
+    /// Analyze every file again, without reading or writing the cache
+    #[arg(long)]
+    pub no_cache: bool,
+    /// Beecrowd report .csv, enables the Beecrowd check
+    #[arg(long)]
+    pub beecrowd: Option<PathBuf>,
+    /// Test cases folder, enables compiling and running submissions
+    #[arg(long)]
+    pub tests: Option<PathBuf>,
+    /// Compile command, with {src} and {out} placeholders
+    #[arg(long)]
+    pub compile: Option<String>,
+    /// Time limit per test case (s)
+    #[arg(long)]
+    pub time_limit: Option<u64>,
+    /// Memory limit per test case (MB)
+    #[arg(long)]
+    pub memory_limit: Option<u64>,
+    /// Report .csv output path, enables the report
+    #[arg(long)]
+    pub report: Option<PathBuf>,
+}
+
+#[derive(Args, Default)]
+pub struct RunArgs {
+    #[command(flatten)]
+    pub steps: StepArgs,
+    /// Google Cloud credentials file
+    #[arg(long)]
+    pub credentials: Option<PathBuf>,
+    /// Classroom course ID
+    #[arg(long)]
+    pub course: Option<String>,
+    /// Classroom coursework ID
+    #[arg(long)]
+    pub work: Option<String>,
+    /// Only download submissions that are new or changed since the last sync
+    #[arg(long)]
+    pub incremental: bool,
+    /// Write computed grades to Classroom (draft or assigned)
+    #[arg(long)]
+    pub push_grades: Option<GradeMode>,
+    /// Return submissions to students after grading
+    #[arg(long)]
+    pub return_submissions: bool,
+    /// Only preview the grade changes, without writing them
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Write grades without asking for confirmation (required outside interactive mode)
+    #[arg(long)]
+    pub yes: bool,
+}
+
+#[derive(Args)]
+pub struct AnalyzeArgs {
+    #[command(flatten)]
+    pub steps: StepArgs,
+    /// Folder with one zip or folder per student, or an already downloaded assignment
+    #[arg(long)]
+    pub source: Option<PathBuf>,
+    /// Folder the normalized submissions are written to (defaults to the source itself)
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+    /// Student roster .csv with `email` and `name` columns
+    #[arg(long)]
+    pub roster: Option<PathBuf>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct SimilarityConfig {
+    pub threshold: Option<u32>,
+    pub method: Option<SimilarityMethod>,
+    pub unknown: Option<UnknownQuestionPolicy>,
+    pub baseline: Option<PathBuf>,
+    pub corpus: Option<CorpusConfig>,
+    pub cache: Option<PathBuf>,
+    pub no_cache: bool,
+    pub scoring: ScoringConfig,
+    pub winnowing: WinnowingConfig,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CorpusConfig {
+    pub dir: PathBuf,
+    pub course: Option<String>,
+    pub assignment: Option<String>,
+    pub archive: bool,
+}
+
+impl Default for CorpusConfig {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from("./corpus"),
+            course: None,
+            assignment: None,
+            archive: false,
+        }
+    }
+}
+
+impl CorpusConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        match self.assignment.as_deref().map(str::trim) {
+            None | Some("") => Err(
+                "assignment is required, use a label shared by every offering (e.g. \"lab3\")"
+                    .to_string(),
+            ),
+            Some(_) => Ok(()),
+        }
+    }
+
+    pub fn options(&self, dir: &Path) -> CorpusOptions {
+        let course = self.course.clone().unwrap_or_else(|| {
+            dir.parent()
+                .and_then(|p| p.file_name())
+                .map_or("local".to_string(), |n| n.to_string_lossy().to_string())
+        });
+
+        CorpusOptions {
+            dir: self.dir.clone(),
+            course,
+            assignment: self.assignment.clone().unwrap_or_default(),
+            archive: self.archive,
+        }
+    }
+}
+
+impl SimilarityConfig {
+    pub fn options(&self, dir: &Path) -> SimilarityOptions {
+        SimilarityOptions {
+            threshold: self.threshold.unwrap_or(100),
+            unknown_policy: self.unknown.unwrap_or_default(),
+            method: self.method.unwrap_or_default(),
+            baseline_dir: self.baseline.clone(),
+            corpus: self.corpus_options(dir),
+            cache_dir: self.cache_dir(),
+            scoring: self.scoring.clone(),
+            winnowing: self.winnowing.clone(),
+        }
+    }
+
+    pub fn cache_dir(&self) -> Option<PathBuf> {
+        (!self.no_cache).then(|| {
+            self.cache
+                .clone()
+                .unwrap_or_else(|| PathBuf::from(DEFAULT_CACHE))
+        })
+    }
+
+    pub fn corpus_options(&self, dir: &Path) -> Option<CorpusOptions> {
+        self.corpus.as_ref().map(|c| c.options(dir))
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct JudgeConfig {
+    pub tests: Option<PathBuf>,
+    pub compile: Option<String>,
+    pub time_limit: Option<u64>,
+    pub memory_limit: Option<u64>,
+    pub compile_time_limit: Option<u64>,
+}
+
+impl JudgeConfig {
+    pub fn options(&self) -> JudgeOptions {
+        let defaults = JudgeOptions::default();
+
+        JudgeOptions {
+            compile_command: self.compile.clone().unwrap_or(defaults.compile_command),
+            tests_dir: self.tests.clone().unwrap_or(defaults.tests_dir),
+            time_limit: self
+                .time_limit
+                .map_or(defaults.time_limit, Duration::from_secs),
+            memory_limit_mb: self.memory_limit.unwrap_or(defaults.memory_limit_mb),
+            compile_time_limit: self
+                .compile_time_limit
+                .map_or(defaults.compile_time_limit, Duration::from_secs),
+        }
+    }
+}
+
+#[derive(Deserialize, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct GradesConfig {
+    pub mode: GradeMode,
+    #[serde(rename = "return")]
+    pub return_submissions: bool,
+    pub dry_run: bool,
+    #[serde(skip)]
+    pub yes: bool,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ApiConfig {
+    pub max_concurrent: Option<usize>,
+    pub requests_per_second: Option<f64>,
+    pub max_retries: Option<u32>,
+    pub classroom_url: Option<String>,
+    pub drive_url: Option<String>,
+}
+
+impl ApiConfig {
+    pub fn options(&self) -> ApiOptions {
+        let defaults = ApiOptions::default();
+
+        ApiOptions {
+            max_concurrent: self.max_concurrent.unwrap_or(defaults.max_concurrent),
+            requests_per_second: self.requests_per_second.or(defaults.requests_per_second),
+            max_retries: self.max_retries.unwrap_or(defaults.max_retries),
+            classroom_url: self.classroom_url.clone().unwrap_or(defaults.classroom_url),
+            drive_url: self.drive_url.clone().unwrap_or(defaults.drive_url),
+            ..defaults
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub credentials: Option<PathBuf>,
+    pub course: Option<String>,
+    pub work: Option<String>,
+    pub incremental: bool,
+    pub languages: Vec<Language>,
+    pub source: Option<PathBuf>,
+    pub output: Option<PathBuf>,
+    pub roster: Option<PathBuf>,
+    pub similarity: Option<SimilarityConfig>,
+    pub beecrowd: Option<PathBuf>,
+    pub judge: Option<JudgeConfig>,
+    pub report: Option<PathBuf>,
+    pub grades: Option<GradesConfig>,
+    pub rubric: Rubric,
+    pub api: ApiConfig,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("could not read {}: {e}", path.display()))?;
+
+        let config: Config = toml::from_str(&content)?;
+
+        config
+            .validate()
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        self.rubric
+            .validate()
+            .map_err(|e| format!("invalid [rubric]: {e}"))?;
+
+        if let Some(similarity) = &self.similarity {
+            if similarity.threshold.is_some_and(|t| t > 100) {
+                return Err("invalid [similarity]: threshold must be at most 100".to_string());
+            }
+
+            similarity
+                .scoring
+                .validate()
+                .map_err(|e| format!("invalid [similarity.scoring]: {e}"))?;
+
+            similarity
+                .winnowing
+                .validate()
+                .map_err(|e| format!("invalid [similarity.winnowing]: {e}"))?;
+
+            if let Some(corpus) = &similarity.corpus {
+                corpus
+                    .validate()
+                    .map_err(|e| format!("invalid [similarity.corpus]: {e}"))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn from_steps(args: StepArgs) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let mut config = match &args.config {
+            Some(path) => Config::load(path)?,
+            None if Path::new(DEFAULT_CONFIG).exists() => Config::load(Path::new(DEFAULT_CONFIG))?,
+            None => Config::default(),
+        };
+
+        if !args.languages.is_empty() {
+            config.languages = args.languages;
+        }
+
+        config.beecrowd = args.beecrowd.or(config.beecrowd);
+        config.report = args.report.or(config.report);
+
+        if args.threshold.is_some()
+            || args.method.is_some()
+            || args.unknown.is_some()
+            || args.baseline.is_some()
+            || args.cache.is_some()
+            || args.no_cache
+        {
+            let similarity = config.similarity.get_or_insert_default();
+            similarity.threshold = args.threshold.or(similarity.threshold);
+            similarity.method = args.method.or(similarity.method);
+            similarity.unknown = args.unknown.or(similarity.unknown);
+            similarity.baseline = args.baseline.or(similarity.baseline.take());
+            similarity.cache = args.cache.or(similarity.cache.take());
+            similarity.no_cache |= args.no_cache;
+        }
+
+        if args.corpus.is_some()
+            || args.corpus_course.is_some()
+            || args.corpus_assignment.is_some()
+            || args.archive
+        {
+            let corpus = config
+                .similarity
+                .get_or_insert_default()
+                .corpus
+                .get_or_insert_default();
+            if let Some(dir) = args.corpus {
+                corpus.dir = dir;
+            }
+
+            corpus.course = args.corpus_course.or(corpus.course.take());
+            corpus.assignment = args.corpus_assignment.or(corpus.assignment.take());
+            corpus.archive |= args.archive;
+        }
+
+        if args.tests.is_some()
+            || args.compile.is_some()
+            || args.time_limit.is_some()
+            || args.memory_limit.is_some()
+        {
+            let judge = config.judge.get_or_insert_default();
+            judge.tests = args.tests.or(judge.tests.take());
+            judge.compile = args.compile.or(judge.compile.take());
+            judge.time_limit = args.time_limit.or(judge.time_limit);
+            judge.memory_limit = args.memory_limit.or(judge.memory_limit);
+        }
+
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    pub fn from_run_args(args: RunArgs) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let mut config = Config::from_steps(args.steps)?;
+
+        config.credentials = args.credentials.or(config.credentials);
+        config.course = args.course.or(config.course);
+        config.work = args.work.or(config.work);
+        config.incremental |= args.incremental;
+
+        if args.push_grades.is_some() || args.return_submissions || args.dry_run {
+            let grades = config.grades.get_or_insert_default();
+            grades.mode = args.push_grades.unwrap_or(grades.mode);
+            grades.return_submissions |= args.return_submissions;
+            grades.dry_run |= args.dry_run;
+        }
+
+        if let Some(grades) = &mut config.grades {
+            grades.yes = args.yes;
+        }
+
+        Ok(config)
+    }
+
+    pub fn from_analyze_args(
+        args: AnalyzeArgs,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let mut config = Config::from_steps(args.steps)?;
+
+        config.source = args.source.or(config.source);
+        config.output = args.output.or(config.output);
+        config.roster = args.roster.or(config.roster);
+
+        Ok(config)
+    }
+
+    pub fn languages(&self) -> Vec<Language> {
+        if self.languages.is_empty() {
+            vec![Language::C]
+        } else {
+            self.languages.clone()
+        }
+    }
+
+    pub fn has_steps(&self) -> bool {
+        self.similarity.is_some()
+            || self.beecrowd.is_some()
+            || self.judge.is_some()
+            || self.report.is_some()
+            || self.grades.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> Config {
+        toml::from_str(content).unwrap()
+    }
+
+    #[test]
+    fn threshold_is_a_percentage() {
+        assert!(parse("[similarity]\nthreshold = 100\n").validate().is_ok());
+        assert!(parse("[similarity]\nthreshold = 0\n").validate().is_ok());
+        assert!(parse("[similarity]\nthreshold = 101\n").validate().is_err());
+
+        let args = StepArgs {
+            threshold: Some(150),
+            ..StepArgs::default()
+        };
+        assert!(Config::from_steps(args).is_err());
+    }
+
+    #[test]
+    fn invalid_sections_are_rejected() {
+        for content in [
+            "[rubric]\nlate = 2.0\n",
+            "[rubric]\nunknown_key = 1.0\n",
+            "[similarity.winnowing]\nkgram = 0\n",
+            "[similarity.scoring.metrics]\nlevenshtein = 0.0\njaccard = 0.0\nlcs = 0.0\ncosine = 0.0\n",
+        ] {
+            let valid = toml::from_str::<Config>(content).map(|c| c.validate());
+            assert!(!matches!(valid, Ok(Ok(()))), "{content}");
+        }
+
+        assert!(Config::default().validate().is_ok());
+    }
+
+    #[test]
+    fn corpus_requires_an_assignment_label() {
+        let config = parse("[similarity.corpus]\ndir = \"./corpus\"\n");
+        assert!(config.validate().is_err());
+
+        let config = parse("[similarity.corpus]\nassignment = \" \"\n");
+        assert!(config.validate().is_err());
+
+        let config = parse("[similarity.corpus]\nassignment = \"lab3\"\n");
+        assert!(config.validate().is_ok());
+
+        let options = config
+            .similarity
+            .unwrap()
+            .corpus_options(Path::new("./submissions/123/456"))
+            .unwrap();
+        assert_eq!(options.course, "123");
+        assert_eq!(options.assignment, "lab3");
+    }
+
+    #[test]
+    fn corpus_flags_are_validated() {
+        let args = StepArgs {
+            corpus: Some(PathBuf::from("./corpus")),
+            ..StepArgs::default()
+        };
+        assert!(Config::from_steps(args).is_err());
+
+        let args = StepArgs {
+            corpus: Some(PathBuf::from("./corpus")),
+            corpus_assignment: Some("lab3".to_string()),
+            ..StepArgs::default()
+        };
+        assert!(Config::from_steps(args).is_ok());
+    }
+}
@@ -1,31 +1,49 @@
+mod config;
+
+use clap::Parser;
 use colored::Colorize;
 use dialoguer::{
-    Input, MultiSelect, Select,
+    Confirm, Input, MultiSelect, Select,
     console::{Style, style},
     theme::ColorfulTheme,
 };
 use indicatif::{ProgressBar, ProgressStyle};
-use std::{collections::HashMap, path::Path, sync::Arc};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 use tokio::sync::{Mutex, mpsc};
 
 use app::{
     beecrowd_parser::beecrowd_report_parser,
-    classroom_downloader::{DownloadEvent, download_classroom_submissions},
-    similarity_checker::{SimilarityEvent, similarity_analyzer},
+    classroom_downloader::{DownloadEvent, DownloadOptions, download_submissions},
+    grade_publisher::{GradeMode, PublishEvent, plan_grades, publish_grades},
+    judge::{JudgeEvent, JudgeOptions, judge_submissions},
+    local_loader::{ImportEvent, LocalFolder, import_local_submissions, load_roster},
+    similarity_checker::{
+        SimilarityEvent, SimilarityMethod, SimilarityOptions, UnknownQuestionPolicy,
+        similarity_analyzer,
+    },
+    submission_source::SubmissionSource,
+    sync_manifest::MANIFEST_FILE,
 };
-use classroom::{api::ClassroomApi, client::ClassroomClient};
-use reporter::{SubmissionResult, generate_report};
-
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let mut client = ClassroomClient::new();
-    client.auth("./credentials.json").await?;
-    let api = Arc::new(ClassroomApi::new(client));
-
-    let courses = api.list_courses().await?;
-    let course_selection: Vec<&str> = courses.courses.iter().map(|c| c.name.as_str()).collect();
+use classroom::{
+    api::ClassroomApi,
+    client::{ClassroomClient, GRADE_SCOPES},
+};
+use config::{AnalyzeArgs, Cli, Command, Config, DEFAULT_CACHE, GradesConfig, RunArgs};
+use reporter::{
+    SubmissionResult,
+    clusters::{PlagiarismCluster, generate_cluster_report},
+    compute_scores, expected_questions, generate_report,
+    scoring::{Rubric, ScoreBreakdown},
+};
+use similarity::{SimilarityConfig, WinnowingConfig};
 
-    let own_theme = ColorfulTheme {
+fn own_theme() -> ColorfulTheme {
+    ColorfulTheme {
         active_item_style: Style::new().for_stderr().green().bold(),
         checked_item_prefix: style("  [x]".to_string()).for_stderr().green().bold(),
         unchecked_item_prefix: style(" [ ]".to_string()).for_stderr().white(),
@@ -39,128 +57,654 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         prompt_style: Style::new().for_stderr().white(),
         error_prefix: style(" ::".to_string()).for_stderr().red(),
         ..ColorfulTheme::default()
-    };
+    }
+}
 
-    let selection = Select::with_theme(&own_theme)
-        .with_prompt("Select the course")
-        .default(0)
-        .max_length(3)
-        .items(&course_selection[..])
-        .interact()
-        .unwrap();
+fn prompt_beecrowd(
+    theme: &ColorfulTheme,
+) -> Result<Option<PathBuf>, Box<dyn std::error::Error + Send + Sync>> {
+    let files: Vec<_> = std::fs::read_dir(".")?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name().into_string().ok()?;
+            if name.ends_with(".csv") {
+                Some(name)
+            } else {
+                None
+            }
+        })
+        .collect();
 
-    let course = courses.courses.get(selection).unwrap();
+    if files.is_empty() {
+        println!(
+            " :: {} no .csv files found in project root",
+            "Error".red().bold()
+        );
 
-    let works = api.list_course_works(&course.id).await?;
-    let works_selection: Vec<&str> = works.course_work.iter().map(|w| w.title.as_str()).collect();
+        return Ok(None);
+    }
 
-    let selection = Select::with_theme(&own_theme)
-        .with_prompt("Select the assignment")
+    let selection = Select::with_theme(theme)
+        .with_prompt("Beecrowd report .csv file-name")
         .default(0)
         .max_length(5)
-        .items(&works_selection[..])
-        .interact()
-        .unwrap();
+        .items(&files)
+        .interact()?;
+
+    Ok(Some(PathBuf::from(&files[selection])))
+}
+
+fn prompt_similarity(
+    theme: &ColorfulTheme,
+) -> Result<SimilarityOptions, Box<dyn std::error::Error + Send + Sync>> {
+    let threshold = Input::<u32>::with_theme(theme)
+        .with_prompt("Similarity threshold (%)")
+        .validate_with(|input: &u32| -> Result<(), &str> {
+            if *input <= 100 {
+                Ok(())
+            } else {
+                Err("Invalid percentage threshold")
+            }
+        })
+        .default(100)
+        .show_default(true)
+        .allow_empty(false)
+        .interact_text()?;
+
+    let policies = &[
+        ("Compare among themselves", UnknownQuestionPolicy::SameGroup),
+        (
+            "Compare against every question",
+            UnknownQuestionPolicy::CompareAll,
+        ),
+        ("Skip", UnknownQuestionPolicy::Skip),
+    ];
+
+    let selection = Select::with_theme(theme)
+        .with_prompt("Files without a question number")
+        .default(0)
+        .items(policies.iter().map(|p| p.0).collect::<Vec<_>>())
+        .interact()?;
 
-    let work = works.course_work.get(selection).unwrap();
+    let unknown_policy = policies[selection].1;
 
-    let options_selection = &[
+    let methods = &[
+        ("Combined token metrics", SimilarityMethod::Combined),
+        ("Winnowing fingerprints", SimilarityMethod::Winnowing),
+    ];
+
+    let selection = Select::with_theme(theme)
+        .with_prompt("Similarity method")
+        .default(0)
+        .items(methods.iter().map(|m| m.0).collect::<Vec<_>>())
+        .interact()?;
+
+    let method = methods[selection].1;
+
+    let input_baseline: String = Input::with_theme(theme)
+        .with_prompt("Starter code folder (empty for none)")
+        .validate_with(|input: &String| -> Result<(), &str> {
+            if input.is_empty() || Path::new(input).is_dir() {
+                Ok(())
+            } else {
+                Err("Folder not found")
+            }
+        })
+        .allow_empty(true)
+        .interact_text()?;
+
+    Ok(SimilarityOptions {
+        threshold,
+        unknown_policy,
+        method,
+        baseline_dir: (!input_baseline.is_empty()).then(|| PathBuf::from(input_baseline)),
+        corpus: None,
+        cache_dir: None,
+        scoring: SimilarityConfig::default(),
+        winnowing: WinnowingConfig::default(),
+    })
+}
+
+fn prompt_judge(
+    theme: &ColorfulTheme,
+) -> Result<JudgeOptions, Box<dyn std::error::Error + Send + Sync>> {
+    let defaults = JudgeOptions::default();
+
+    let tests_dir: String = Input::with_theme(theme)
+        .with_prompt("Test cases folder")
+        .default(defaults.tests_dir.to_string_lossy().to_string())
+        .validate_with(|input: &String| -> Result<(), &str> {
+            if Path::new(input).is_dir() {
+                Ok(())
+            } else {
+                Err("Folder not found")
+            }
+        })
+        .interact_text()?;
+
+    let compile_command: String = Input::with_theme(theme)
+        .with_prompt("Compile command")
+        .default(defaults.compile_command.clone())
+        .interact_text()?;
+
+    let time_limit = Input::<u64>::with_theme(theme)
+        .with_prompt("Time limit per test (s)")
+        .default(defaults.time_limit.as_secs())
+        .interact_text()?;
+
+    Ok(JudgeOptions {
+        compile_command,
+        tests_dir: PathBuf::from(tests_dir),
+        time_limit: Duration::from_secs(time_limit),
+        ..defaults
+    })
+}
+
+fn prompt_grades(
+    theme: &ColorfulTheme,
+) -> Result<GradesConfig, Box<dyn std::error::Error + Send + Sync>> {
+    let modes = &[
+        ("Draft grade", GradeMode::Draft),
+        ("Assigned grade", GradeMode::Assigned),
+    ];
+
+    let selection = Select::with_theme(theme)
+        .with_prompt("Grade to write")
+        .default(0)
+        .items(modes.iter().map(|m| m.0).collect::<Vec<_>>())
+        .interact()?;
+
+    let return_submissions = Confirm::with_theme(theme)
+        .with_prompt("Return submissions to students")
+        .default(false)
+        .interact()?;
+
+    Ok(GradesConfig {
+        mode: modes[selection].1,
+        return_submissions,
+        dry_run: false,
+        yes: false,
+    })
+}
+
+struct Steps {
+    similarity: Option<SimilarityOptions>,
+    beecrowd: Option<PathBuf>,
+    judge: Option<JudgeOptions>,
+    report: Option<PathBuf>,
+    grades: Option<GradesConfig>,
+}
+
+fn select_steps(
+    config: &Config,
+    theme: &ColorfulTheme,
+    dir: &Path,
+    allow_grades: bool,
+) -> Result<Steps, Box<dyn std::error::Error + Send + Sync>> {
+    if config.has_steps() {
+        return Ok(Steps {
+            similarity: config.similarity.as_ref().map(|s| s.options(dir)),
+            beecrowd: config.beecrowd.clone(),
+            judge: config.judge.as_ref().map(|j| j.options()),
+            report: config.report.clone(),
+            grades: config.grades.clone(),
+        });
+    }
+
+    let mut options_selection = vec![
         ("Check Similarity", true),
         ("Check Beecrowd", true),
         ("Make Report", true),
+        ("Run Tests", false),
     ];
 
-    let selections = MultiSelect::with_theme(&own_theme)
+    if allow_grades {
+        options_selection.push(("Push Grades", false));
+    }
+
+    let selections = MultiSelect::with_theme(theme)
         .with_prompt("Select more options")
         .items_checked(options_selection.iter().copied())
-        .interact()
-        .unwrap();
-
-    let mut input_file = None;
-
-    if selections.contains(&1) {
-        let files: Vec<_> = std::fs::read_dir(".")
-            .unwrap()
-            .filter_map(|entry| {
-                let entry = entry.ok()?;
-                let name = entry.file_name().into_string().ok()?;
-                if name.ends_with(".csv") {
-                    Some(name)
-                } else {
-                    None
+        .interact()?;
+
+    let beecrowd = if selections.contains(&1) {
+        prompt_beecrowd(theme)?
+    } else {
+        None
+    };
+
+    let similarity = if selections.contains(&0) {
+        Some(SimilarityOptions {
+            corpus: config
+                .similarity
+                .as_ref()
+                .and_then(|s| s.corpus_options(dir)),
+            cache_dir: config
+                .similarity
+                .as_ref()
+                .map_or(Some(PathBuf::from(DEFAULT_CACHE)), |s| s.cache_dir()),
+            scoring: config
+                .similarity
+                .as_ref()
+                .map(|s| s.scoring.clone())
+                .unwrap_or_default(),
+            winnowing: config
+                .similarity
+                .as_ref()
+                .map(|s| s.winnowing.clone())
+                .unwrap_or_default(),
+            ..prompt_similarity(theme)?
+        })
+    } else {
+        None
+    };
+
+    let judge = if selections.contains(&3) {
+        Some(prompt_judge(theme)?)
+    } else {
+        None
+    };
+
+    let report = selections.contains(&2).then(|| dir.join("report.csv"));
+
+    let grades = if selections.contains(&4) {
+        Some(prompt_grades(theme)?)
+    } else {
+        None
+    };
+
+    Ok(Steps {
+        similarity,
+        beecrowd,
+        judge,
+        report,
+        grades,
+    })
+}
+
+fn progress_bar() -> Result<ProgressBar, Box<dyn std::error::Error + Send + Sync>> {
+    let bar = ProgressBar::new(0);
+    bar.set_style(
+        ProgressStyle::with_template(
+            " ::{prefix:>12.cyan.bold} [{bar:57}] {pos}/{len} {percent}%",
+        )?
+        .progress_chars("## "),
+    );
+
+    Ok(bar)
+}
+
+async fn analyze(
+    dir: PathBuf,
+    results: Arc<Mutex<HashMap<String, SubmissionResult>>>,
+    steps: &mut Steps,
+    rubric: &Rubric,
+    bar: &ProgressBar,
+) -> Result<HashMap<String, ScoreBreakdown>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut rings: Vec<PlagiarismCluster> = vec![];
+
+    if let Some(options) = steps.similarity.take() {
+        println!(
+            " :: {} all files and generating pairs",
+            "Loading".green().bold()
+        );
+
+        let (tx, mut rx) = mpsc::channel(100);
+
+        let cl = results.clone();
+        let d = dir.clone();
+
+        let handle = tokio::spawn(async move {
+            let mut lock = cl.lock().await;
+            similarity_analyzer(&d, &mut lock, &options, tx).await
+        });
+
+        let mut total_time = 0.0;
+        let mut discounted = vec![];
+        let mut archived = 0;
+        while let Some(e) = rx.recv().await {
+            match e {
+                SimilarityEvent::CacheWarning(message) => {
+                    println!(" :: {} {}", "Cache".yellow().bold(), message);
+                }
+                SimilarityEvent::Discounted(_, d) => discounted.push(d),
+                SimilarityEvent::Clusters(c) => rings = c,
+                SimilarityEvent::Corpus(n) => println!(
+                    " :: {} {} archived submissions from previous offerings",
+                    "Loaded".green().bold(),
+                    n
+                ),
+                SimilarityEvent::StaleCorpus(n) => println!(
+                    " :: {} {} archived submissions from an older analyzer version, archive them again to compare",
+                    "Skipped".yellow().bold(),
+                    n
+                ),
+                SimilarityEvent::Archived(n) => archived = n,
+                SimilarityEvent::Start(n) => {
+                    if !discounted.is_empty() {
+                        println!(
+                            " :: {} starter code from {} files ({:.2}% on average)",
+                            "Discounted".green().bold(),
+                            discounted.len(),
+                            discounted.iter().sum::<f32>() / discounted.len() as f32 * 100.0
+                        );
+                    }
+
+                    bar.reset();
+                    bar.set_prefix("Analyzing");
+                    bar.set_length(n);
                 }
-            })
-            .collect();
+                SimilarityEvent::Progress(n) => bar.inc(n),
+                SimilarityEvent::End(t) => {
+                    total_time = t;
+                    bar.finish();
+                }
+            }
+        }
+
+        handle.await??;
+
+        println!(
+            " :: {} and analyzed all submissions in {:.2}s",
+            "Finished".green().bold(),
+            total_time
+        );
 
-        if files.is_empty() {
+        if archived > 0 {
             println!(
-                " :: {} no .csv files found in project root",
-                "Error".red().bold()
+                " :: {} {} submissions to the corpus",
+                "Archived".green().bold(),
+                archived
             );
-        } else {
-            let selection = Select::with_theme(&own_theme)
-                .with_prompt("Beecrowd report .csv file-name")
-                .default(0)
-                .max_length(5)
-                .items(&files)
-                .interact()
-                .unwrap();
+        }
 
-            input_file = Some(files[selection].clone());
+        if let Some(largest) = rings.first() {
+            println!(
+                " :: {} {} plagiarism clusters (largest has {} submissions)",
+                "Found".yellow().bold(),
+                rings.len(),
+                largest.members.len()
+            );
         }
     }
 
-    let mut input_thr: Result<u32, dialoguer::Error> = Result::Ok(100);
+    if let Some(options) = steps.judge.take() {
+        println!(
+            " :: {} all submissions and running test cases",
+            "Compiling".green().bold()
+        );
 
-    if selections.contains(&0) {
-        input_thr = Input::<u32>::with_theme(&own_theme)
-            .with_prompt("Similarity threshold (%)")
-            .validate_with(|input: &u32| -> Result<(), &str> {
-                if *input <= 100 {
-                    Ok(())
-                } else {
-                    Err("Invalid percentage threshold")
+        let (tx, mut rx) = mpsc::channel(100);
+
+        let cl = results.clone();
+        let d = dir.clone();
+
+        let handle = tokio::spawn(async move {
+            let mut lock = cl.lock().await;
+            judge_submissions(&d, &mut lock, &options, tx).await
+        });
+
+        let mut total_time = 0.0;
+        while let Some(e) = rx.recv().await {
+            match e {
+                JudgeEvent::MissingOutput(path) => println!(
+                    " :: {} {} not found, skipping that test case",
+                    "Tests".yellow().bold(),
+                    path.display()
+                ),
+                JudgeEvent::Start(n) => {
+                    bar.reset();
+                    bar.set_prefix("Judging");
+                    bar.set_length(n);
+                }
+                JudgeEvent::Progress(n) => bar.inc(n),
+                JudgeEvent::End(t) => {
+                    total_time = t;
+                    bar.finish();
                 }
-            })
-            .default(100)
-            .show_default(true)
-            .allow_empty(false)
-            .interact_text();
+            }
+        }
+
+        handle.await??;
+
+        println!(
+            " :: {} and judged all submissions in {:.2}s",
+            "Finished".green().bold(),
+            total_time
+        );
+    }
+
+    let mut results = Arc::try_unwrap(results).unwrap().into_inner();
+
+    if let Some(f) = &steps.beecrowd {
+        beecrowd_report_parser(&mut results, f)?;
+
+        println!(
+            " :: {} parsing and checking Beecrowd report",
+            "Finished".green().bold(),
+        );
     }
 
+    if rubric.expected_questions.is_none() {
+        println!(
+            " :: {} {} expected questions, the most solved by any student (set expected_questions in [rubric])",
+            "Assuming".yellow().bold(),
+            expected_questions(&results, rubric)
+        );
+    }
+
+    let scores = compute_scores(&results, rubric);
+
+    if let Some(path) = &steps.report {
+        let path = path.to_string_lossy();
+
+        generate_report(results, &path, rubric)?;
+
+        println!(
+            " :: {} student report at {}",
+            "Generated".green().bold(),
+            path
+        );
+
+        if !rings.is_empty() {
+            let stem = Path::new(path.as_ref())
+                .file_stem()
+                .map_or("report".into(), |s| s.to_string_lossy());
+            let clusters_path =
+                Path::new(path.as_ref()).with_file_name(format!("{stem}_clusters.csv"));
+
+            generate_cluster_report(&rings, &clusters_path)?;
+
+            println!(
+                " :: {} plagiarism clusters at {}",
+                "Generated".green().bold(),
+                clusters_path.display()
+            );
+        }
+    }
+
+    Ok(scores)
+}
+
+async fn analyze_local(args: AnalyzeArgs) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let config = Config::from_analyze_args(args)?;
+    let own_theme = own_theme();
+
+    let source = match &config.source {
+        Some(source) => source.clone(),
+        None => {
+            let input: String = Input::with_theme(&own_theme)
+                .with_prompt("Submissions folder")
+                .validate_with(|input: &String| -> Result<(), &str> {
+                    if Path::new(input).is_dir() {
+                        Ok(())
+                    } else {
+                        Err("Folder not found")
+                    }
+                })
+                .interact_text()?;
+
+            PathBuf::from(input)
+        }
+    };
+
+    let dest = config.output.clone().unwrap_or_else(|| source.clone());
+
+    let roster = match &config.roster {
+        Some(path) => Some(load_roster(path)?),
+        None => None,
+    };
+
+    let mut steps = select_steps(&config, &own_theme, &dest, false)?;
+
+    let results: Arc<Mutex<HashMap<String, SubmissionResult>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+
+    println!(
+        " :: {} all submissions from {}",
+        "Importing".green().bold(),
+        source.display()
+    );
+
+    let bar = progress_bar()?;
+
+    let (tx, mut rx) = mpsc::channel(100);
+
+    let cl = results.clone();
+    let languages = config.languages();
+    let folder = LocalFolder::new(source.clone(), roster);
+    let out = dest.clone();
+
+    let handle = tokio::spawn(async move {
+        let mut lock = cl.lock().await;
+        import_local_submissions(&folder, &out, &languages, &mut lock, tx).await
+    });
+
+    let mut total_time = 0.0;
+
+    while let Some(e) = rx.recv().await {
+        match e {
+            ImportEvent::Start(n) => {
+                bar.set_prefix("Importing");
+                bar.set_length(n);
+            }
+            ImportEvent::Progress(n) => bar.inc(n),
+            ImportEvent::End(t) => {
+                total_time = t;
+                bar.finish();
+            }
+        }
+    }
+
+    handle.await??;
+
+    println!(
+        " :: {} and formatted all submissions in {:.2}s",
+        "Finished".green().bold(),
+        total_time
+    );
+
+    analyze(dest, results, &mut steps, &config.rubric, &bar).await?;
+
+    Ok(())
+}
+
+async fn run(args: RunArgs) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let config = Config::from_run_args(args)?;
+    let credentials = config
+        .credentials
+        .clone()
+        .unwrap_or_else(|| PathBuf::from("./credentials.json"));
+
+    let mut client = ClassroomClient::new();
+    client.auth(&credentials.to_string_lossy()).await?;
+    let api = Arc::new(ClassroomApi::with_options(client, config.api.options()));
+
+    let own_theme = own_theme();
+
+    let course_id = match &config.course {
+        Some(id) => id.clone(),
+        None => {
+            let courses = api.list_offerings().await?;
+            let course_selection: Vec<&str> = courses.iter().map(|c| c.name.as_str()).collect();
+
+            let selection = Select::with_theme(&own_theme)
+                .with_prompt("Select the course")
+                .default(0)
+                .max_length(3)
+                .items(&course_selection[..])
+                .interact()?;
+
+            courses[selection].id.clone()
+        }
+    };
+
+    let work_id = match &config.work {
+        Some(id) => id.clone(),
+        None => {
+            let works = api.list_assignments(&course_id).await?;
+            let works_selection: Vec<&str> = works.iter().map(|w| w.title.as_str()).collect();
+
+            let selection = Select::with_theme(&own_theme)
+                .with_prompt("Select the assignment")
+                .default(0)
+                .max_length(5)
+                .items(&works_selection[..])
+                .interact()?;
+
+            works[selection].id.clone()
+        }
+    };
+
+    let interactive = !config.has_steps();
+    let dir = PathBuf::from(format!("./submissions/{course_id}/{work_id}"));
+    let mut steps = select_steps(&config, &own_theme, &dir, true)?;
+
+    let download_options = DownloadOptions {
+        incremental: config.incremental
+            || (interactive
+                && dir.join(MANIFEST_FILE).exists()
+                && Confirm::with_theme(&own_theme)
+                    .with_prompt("Only download new or changed submissions")
+                    .default(true)
+                    .interact()?),
+        languages: config.languages(),
+        ..DownloadOptions::default()
+    };
+
     let results: Arc<Mutex<HashMap<String, SubmissionResult>>> =
         Arc::new(Mutex::new(HashMap::new()));
 
     println!(
         " :: {} all students and submissions [CID {}/AID {}]",
         "Fetching".green().bold(),
-        course.id,
-        work.id
+        course_id,
+        work_id
     );
 
-    let bar = ProgressBar::new(0);
-    bar.set_style(
-        ProgressStyle::with_template(
-            " ::{prefix:>12.cyan.bold} [{bar:57}] {pos}/{len} {percent}%",
-        )?
-        .progress_chars("## "),
-    );
+    let bar = progress_bar()?;
 
     let (tx, mut rx) = mpsc::channel(100);
 
     let cl = results.clone();
-    let cid = course.id.clone();
-    let wid = work.id.clone();
+    let cid = course_id.clone();
+    let wid = work_id.clone();
 
-    tokio::spawn(async move {
+    let handle = tokio::spawn(async move {
         let mut lock = cl.lock().await;
-        download_classroom_submissions(api.clone(), &cid, &wid, &mut lock, tx).await
+        download_submissions(api.clone(), &cid, &wid, &download_options, &mut lock, tx).await
     });
 
     let mut total_time = 0.0;
+    let mut unchanged = 0;
+    let mut resubmitted = vec![];
+    let mut failures = vec![];
 
     while let Some(e) = rx.recv().await {
         match e {
+            DownloadEvent::Resubmitted(email) => resubmitted.push(email),
+            DownloadEvent::Failed(email, message) => failures.push((email, message)),
+            DownloadEvent::Unchanged(n) => unchanged = n,
             DownloadEvent::Start(n) => {
                 bar.set_prefix("Downloading");
                 bar.set_length(n);
@@ -173,78 +717,157 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         }
     }
 
+    handle.await??;
+
     println!(
         " :: {} and formatted all submissions in {:.2}s",
         "Finished".green().bold(),
         total_time
     );
 
-    if selections.contains(&0) {
-        if let Ok(thr) = input_thr {
+    if unchanged > 0 {
+        println!(
+            " :: {} {} unchanged submissions since the last sync",
+            "Skipped".green().bold(),
+            unchanged
+        );
+    }
+
+    for email in resubmitted {
+        println!(" :: {} {}", "Resubmitted".yellow().bold(), email);
+    }
+
+    for (email, message) in &failures {
+        println!(" :: {} {}: {}", "Failed".red().bold(), email, message);
+    }
+
+    let scores = analyze(dir, results, &mut steps, &config.rubric, &bar).await?;
+
+    if let Some(grades) = steps.grades {
+        let mut client = ClassroomClient::new();
+        client
+            .auth_with_scopes(&credentials.to_string_lossy(), GRADE_SCOPES)
+            .await?;
+        let api = Arc::new(ClassroomApi::with_options(client, config.api.options()));
+
+        let changes = plan_grades(&api, &course_id, &work_id, &scores).await?;
+        let changed = changes.iter().filter(|c| c.is_changed(grades.mode)).count();
+
+        for change in &changes {
+            let current = change
+                .current(grades.mode)
+                .map_or("-".to_string(), |g| format!("{g:.2}"));
+
+            let marker = if change.is_changed(grades.mode) {
+                "→".yellow().bold()
+            } else {
+                "=".white()
+            };
+
             println!(
-                " :: {} all files and generating pairs",
-                "Loading".green().bold()
+                " :: {:<40} {:>8} {} {:.2}",
+                change.email, current, marker, change.new_grade
             );
+        }
 
-            let (tx, mut rx) = mpsc::channel(100);
+        let returned = if grades.return_submissions {
+            changes.iter().filter(|c| c.returnable).count()
+        } else {
+            0
+        };
+
+        println!(
+            " :: {} {} of {} grades would change, {} submissions would be returned",
+            "Preview".green().bold(),
+            changed,
+            changes.len(),
+            returned
+        );
 
-            let cl = results.clone();
-            let cid = course.id.clone();
-            let wid = work.id.clone();
+        let confirmed = !grades.dry_run
+            && changed + returned > 0
+            && if interactive {
+                Confirm::with_theme(&own_theme)
+                    .with_prompt(format!(
+                        "Write {changed} grades and return {returned} submissions"
+                    ))
+                    .default(false)
+                    .interact()?
+            } else {
+                if !grades.yes {
+                    println!(
+                        " :: {} writing grades, pass --yes to write them without a prompt",
+                        "Skipped".yellow().bold()
+                    );
+                }
+
+                grades.yes
+            };
+
+        if confirmed {
+            let (tx, mut rx) = mpsc::channel(100);
 
-            tokio::spawn(async move {
-                let mut lock = cl.lock().await;
-                similarity_analyzer(&cid, &wid, &mut lock, thr, tx).await
+            let cid = course_id.clone();
+            let wid = work_id.clone();
+
+            let handle = tokio::spawn(async move {
+                publish_grades(
+                    api,
+                    &cid,
+                    &wid,
+                    &changes,
+                    grades.mode,
+                    grades.return_submissions,
+                    tx,
+                )
+                .await
             });
 
             let mut total_time = 0.0;
+            let mut failures = vec![];
             while let Some(e) = rx.recv().await {
                 match e {
-                    SimilarityEvent::Start(n) => {
+                    PublishEvent::Start(n) => {
                         bar.reset();
-                        bar.set_prefix("Analyzing");
+                        bar.set_prefix("Grading");
                         bar.set_length(n);
                     }
-                    SimilarityEvent::Progress(n) => bar.inc(n),
-                    SimilarityEvent::End(t) => {
+                    PublishEvent::Progress(n) => bar.inc(n),
+                    PublishEvent::Failed(email, message) => failures.push((email, message)),
+                    PublishEvent::End(t) => {
                         total_time = t;
                         bar.finish();
                     }
                 }
             }
 
-            println!(
-                " :: {} and analyzed all submissions in {:.2}s",
-                "Finished".green().bold(),
-                total_time
-            );
-        }
-    }
-
-    let mut results = Arc::try_unwrap(results).unwrap().into_inner();
+            handle.await??;
 
-    if selections.contains(&1) {
-        if let Some(f) = input_file {
-            beecrowd_report_parser(&mut results, Path::new(&f)).unwrap();
+            for (email, message) in &failures {
+                println!(" :: {} {}: {}", "Failed".red().bold(), email, message);
+            }
 
             println!(
-                " :: {} parsing and checking Beecrowd report",
+                " :: {} and published {} grades in {:.2}s",
                 "Finished".green().bold(),
+                if failures.is_empty() {
+                    "all"
+                } else {
+                    "the remaining"
+                },
+                total_time
             );
         }
     }
 
-    if selections.contains(&2) {
-        let path = format!("./submissions/{}/{}/report.csv", course.id, work.id);
-
-        generate_report(results, &path)?;
+    Ok(())
+}
 
-        println!(
-            " :: {} student report at {}",
-            "Generated".green().bold(),
-            path
-        );
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    match Cli::parse().command {
+        Some(Command::Run(args)) => run(args).await,
+        Some(Command::Analyze(args)) => analyze_local(args).await,
+        None => run(RunArgs::default()).await,
     }
-
-    Ok(())
 }
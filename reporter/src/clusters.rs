@@ -0,0 +1,108 @@
+use serde::Serialize;
+use std::path::Path;
+
+#[derive(Debug, Clone)]
+pub struct PlagiarismCluster {
+    pub question: String,
+    pub members: Vec<(String, String)>,
+    pub average: f32,
+    pub central: String,
+}
+
+impl PlagiarismCluster {
+    #[must_use]
+    pub fn describe(&self) -> String {
+        format!(
+            "PLAGIARISM RING {}: {} SUBMISSIONS, AVERAGE {:.2}%, LIKELY ORIGINAL {}",
+            self.question,
+            self.members.len(),
+            self.average * 100.0,
+            self.central
+        )
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct ClusterRecord {
+    question: String,
+    size: usize,
+    #[serde(rename = "average percent")]
+    average_percent: f32,
+    #[serde(rename = "likely original")]
+    likely_original: String,
+    members: String,
+}
+
+pub fn generate_cluster_report(
+    clusters: &[PlagiarismCluster],
+    path: &Path,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut wtr = csv::Writer::from_path(path)?;
+
+    for cluster in clusters {
+        wtr.serialize(ClusterRecord {
+            question: cluster.question.clone(),
+            size: cluster.members.len(),
+            average_percent: (cluster.average * 10000.0).round() / 100.0,
+            likely_original: cluster.central.clone(),
+            members: cluster
+                .members
+                .iter()
+                .map(|(email, _)| email.as_str())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        })?;
+    }
+
+    wtr.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cluster_report_writes_percentages() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("clusters.csv");
+
+        let cluster = PlagiarismCluster {
+            question: "q1".to_string(),
+            members: vec![
+                (
+                    "ana@school.edu".to_string(),
+                    "q1_ana@school.edu.c".to_string(),
+                ),
+                (
+                    "bia@school.edu".to_string(),
+                    "q1_bia@school.edu.c".to_string(),
+                ),
+                (
+                    "caio@school.edu".to_string(),
+                    "q1_caio@school.edu.c".to_string(),
+                ),
+            ],
+            average: 0.87654,
+            central: "q1_bia@school.edu.c".to_string(),
+        };
+
+        generate_cluster_report(std::slice::from_ref(&cluster), &path).unwrap();
+
+        let mut reader = csv::Reader::from_path(&path).unwrap();
+        let headers = reader.headers().unwrap().clone();
+        let record = reader.records().next().unwrap().unwrap();
+        let field = |name: &str| &record[headers.iter().position(|h| h == name).unwrap()];
+
+        assert_eq!(field("question"), "q1");
+        assert_eq!(field("size"), "3");
+        assert_eq!(field("average percent"), "87.65");
+        assert_eq!(field("likely original"), "q1_bia@school.edu.c");
+        assert_eq!(
+            field("members"),
+            "ana@school.edu\nbia@school.edu\ncaio@school.edu"
+        );
+        assert!(cluster.describe().contains("AVERAGE 87.65%"));
+    }
+}
@@ -0,0 +1,320 @@
+use std::{fs, path::Path};
+
+use similarity::{Fragment, Language, MetricBreakdown, SimilarityReport};
+
+const COLORS: &[&str] = &[
+    "#ffd54f", "#81d4fa", "#a5d6a7", "#f48fb1", "#ce93d8", "#ffab91", "#80cbc4", "#e6ee9c",
+];
+
+pub struct EvidencePair {
+    pub left_name: String,
+    pub right_name: String,
+    pub left_source: String,
+    pub right_source: String,
+    pub report: SimilarityReport,
+    pub metrics: Vec<(String, f32)>,
+    pub fragments: Vec<Fragment>,
+}
+
+impl EvidencePair {
+    #[must_use]
+    pub fn page_name(&self) -> String {
+        let stem = |n: &str| {
+            let path = Path::new(n);
+
+            match path.file_stem() {
+                Some(stem) if Language::from_path(path).is_some() => {
+                    stem.to_string_lossy().to_string()
+                }
+                _ => n.to_string(),
+            }
+        };
+        format!("{}__{}.html", stem(&self.left_name), stem(&self.right_name))
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn highlight(source: &str, ranges: &[(usize, usize, usize)]) -> String {
+    let mut colors: Vec<Option<usize>> = vec![None; source.len()];
+
+    for &(start, end, color) in ranges {
+        for c in colors
+            .iter_mut()
+            .take(end.min(source.len()))
+            .skip(start)
+            .filter(|c| c.is_none())
+        {
+            *c = Some(color);
+        }
+    }
+
+    let mut out = String::new();
+    let mut offset = 0;
+
+    for (n, line) in source.split('\n').enumerate() {
+        out.push_str(&format!("<span class=\"ln\">{:>4}</span> ", n + 1));
+
+        let mut current: Option<usize> = None;
+        let mut chunk = String::new();
+
+        for (i, ch) in line.char_indices() {
+            let color = colors[offset + i];
+
+            if color != current {
+                flush(&mut out, &chunk, current);
+                chunk.clear();
+                current = color;
+            }
+
+            chunk.push(ch);
+        }
+
+        flush(&mut out, &chunk, current);
+        out.push('\n');
+        offset += line.len() + 1;
+    }
+
+    out
+}
+
+fn flush(out: &mut String, chunk: &str, color: Option<usize>) {
+    if chunk.is_empty() {
+        return;
+    }
+
+    match color {
+        Some(c) => out.push_str(&format!(
+            "<span class=\"m\" style=\"background:{}\">{}</span>",
+            COLORS[c % COLORS.len()],
+            escape(chunk)
+        )),
+        None => out.push_str(&escape(chunk)),
+    }
+}
+
+fn breakdown_row(label: &str, metrics: &MetricBreakdown) -> String {
+    let cells = metrics
+        .values()
+        .iter()
+        .map(|(name, value)| format!("{name} {:.2}%", value * 100.0))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!(
+        "<tr><td>{}</td><td>{:.2}%</td><td>{cells}</td></tr>",
+        escape(label),
+        metrics.combined * 100.0
+    )
+}
+
+fn breakdown(report: &SimilarityReport) -> String {
+    let mut rows = String::new();
+
+    if let Some(file) = &report.file {
+        rows.push_str(&breakdown_row("Whole file", file));
+    }
+
+    if let Some(functions) = &report.functions {
+        rows.push_str(&format!(
+            "<tr><td>Matched functions</td><td>{:.2}%</td><td></td></tr>",
+            functions.score * 100.0
+        ));
+
+        for m in &functions.matches {
+            rows.push_str(&breakdown_row(
+                &format!("{} and {}", m.left, m.right),
+                &m.metrics,
+            ));
+        }
+    }
+
+    if rows.is_empty() {
+        return rows;
+    }
+
+    format!("<h2>Breakdown</h2>\n<table>{rows}</table>")
+}
+
+fn render_page(pair: &EvidencePair) -> String {
+    let left_ranges: Vec<_> = pair
+        .fragments
+        .iter()
+        .enumerate()
+        .map(|(i, f)| (f.left.start_byte, f.left.end_byte, i))
+        .collect();
+    let right_ranges: Vec<_> = pair
+        .fragments
+        .iter()
+        .enumerate()
+        .map(|(i, f)| (f.right.start_byte, f.right.end_byte, i))
+        .collect();
+
+    let metrics = pair
+        .metrics
+        .iter()
+        .map(|(name, value)| {
+            format!(
+                "<tr><td>{}</td><td>{:.2}%</td></tr>",
+                escape(name),
+                value * 100.0
+            )
+        })
+        .collect::<String>();
+
+    let breakdown = breakdown(&pair.report);
+
+    let fragments = pair
+        .fragments
+        .iter()
+        .enumerate()
+        .map(|(i, f)| {
+            format!(
+                "<li><span class=\"m\" style=\"background:{}\">&nbsp;&nbsp;</span> lines {}-{} of {} match lines {}-{} of {} ({} tokens)</li>",
+                COLORS[i % COLORS.len()],
+                f.left.start_line,
+                f.left.end_line,
+                escape(&pair.left_name),
+                f.right.start_line,
+                f.right.end_line,
+                escape(&pair.right_name),
+                f.tokens
+            )
+        })
+        .collect::<String>();
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{left} vs {right}</title>
+<style>
+body {{ font-family: sans-serif; margin: 1em; }}
+.files {{ display: flex; gap: 1em; }}
+.files > div {{ flex: 1; min-width: 0; }}
+pre {{ background: #fafafa; border: 1px solid #ddd; padding: .5em; overflow-x: auto; }}
+.ln {{ color: #999; user-select: none; }}
+table {{ border-collapse: collapse; }}
+td {{ border: 1px solid #ddd; padding: .2em .6em; }}
+</style>
+</head>
+<body>
+<p><a href="index.html">Back to index</a></p>
+<h1>{left} vs {right} ({score:.2}%)</h1>
+<table>{metrics}</table>
+{breakdown}
+<ul>{fragments}</ul>
+<div class="files">
+<div><h2>{left}</h2><pre>{left_code}</pre></div>
+<div><h2>{right}</h2><pre>{right_code}</pre></div>
+</div>
+</body>
+</html>
+"#,
+        left = escape(&pair.left_name),
+        right = escape(&pair.right_name),
+        score = pair.report.score * 100.0,
+        left_code = highlight(&pair.left_source, &left_ranges),
+        right_code = highlight(&pair.right_source, &right_ranges),
+    )
+}
+
+fn render_index(pairs: &[EvidencePair]) -> String {
+    let rows = pairs
+        .iter()
+        .map(|p| {
+            format!(
+                "<tr><td><a href=\"{}\">{}</a></td><td>{}</td><td>{:.2}%</td><td>{}</td></tr>",
+                escape(&p.page_name()),
+                escape(&p.left_name),
+                escape(&p.right_name),
+                p.report.score * 100.0,
+                p.fragments.len()
+            )
+        })
+        .collect::<String>();
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Similarity evidence</title>
+<style>
+body {{ font-family: sans-serif; margin: 1em; }}
+table {{ border-collapse: collapse; }}
+td, th {{ border: 1px solid #ddd; padding: .2em .6em; text-align: left; }}
+</style>
+</head>
+<body>
+<h1>Similarity evidence</h1>
+<table>
+<tr><th>File</th><th>Matched with</th><th>Score</th><th>Fragments</th></tr>
+{rows}
+</table>
+</body>
+</html>
+"#
+    )
+}
+
+pub fn generate_evidence(
+    pairs: &mut [EvidencePair],
+    dir: &Path,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    if dir.exists() {
+        fs::remove_dir_all(dir)?;
+    }
+
+    fs::create_dir_all(dir)?;
+
+    pairs.sort_by(|a, b| b.report.score.total_cmp(&a.report.score));
+
+    for pair in pairs.iter() {
+        fs::write(dir.join(pair.page_name()), render_page(pair))?;
+    }
+
+    fs::write(dir.join("index.html"), render_index(pairs))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pair(left: &str, right: &str) -> EvidencePair {
+        EvidencePair {
+            left_name: left.to_string(),
+            right_name: right.to_string(),
+            left_source: String::new(),
+            right_source: String::new(),
+            report: SimilarityReport::from_score(1.0),
+            metrics: vec![],
+            fragments: vec![],
+        }
+    }
+
+    #[test]
+    fn page_name_strips_known_source_extensions() {
+        for (left, right) in [
+            ("q1_a.c", "q1_b.c"),
+            ("q1_a.cpp", "q1_b.cc"),
+            ("q1_a.java", "q1_b.java"),
+            ("q1_a.py", "q1_b.PY"),
+        ] {
+            assert_eq!(pair(left, right).page_name(), "q1_a__q1_b.html");
+        }
+
+        assert_eq!(
+            pair("q1_a.txt", "q1_b.c").page_name(),
+            "q1_a.txt__q1_b.html"
+        );
+    }
+}
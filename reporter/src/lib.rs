@@ -1,8 +1,17 @@
+pub mod clusters;
+pub mod evidence;
+pub mod scoring;
+
 use std::collections::HashMap;
 
 use serde::Serialize;
 
-use classroom::models::Student;
+use similarity::{Fragment, SimilarityReport};
+
+use scoring::{Rubric, ScoreBreakdown, score};
+
+/// Function pairs listed in a plagiarism comment, closest first.
+const LISTED_FUNCTION_MATCHES: usize = 5;
 
 #[derive(Serialize, Debug)]
 struct Record {
@@ -10,15 +19,44 @@ struct Record {
     email: String,
     #[serde(rename = "score percent")]
     score_percent: f32,
+    #[serde(rename = "score breakdown")]
+    score_breakdown: String,
     comments: String,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Student {
+    pub id: String,
+    pub email: String,
+    pub name: String,
+}
+
 #[derive(Debug)]
 pub struct SubmissionResult {
     pub student: Student,
     pub comments: Vec<String>,
     pub errors: Vec<SubmissionError>,
     pub solved: i32,
+    pub tests: Vec<TestReport>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Accepted,
+    WrongAnswer,
+    RuntimeError,
+    TimeLimitExceeded,
+    MemoryLimitExceeded,
+    OutputLimitExceeded,
+    JudgeError,
+}
+
+#[derive(Debug, Clone)]
+pub struct TestReport {
+    pub file: String,
+    pub compiled: bool,
+    pub diagnostics: Option<String>,
+    pub verdicts: Vec<Verdict>,
 }
 
 #[derive(Debug)]
@@ -27,14 +65,20 @@ pub enum SubmissionError {
     InvalidSubmission,
     InvalidZip,
     InvalidFormat,
-    PlagiarismDetected(String, String, f32),
+    PlagiarismDetected(String, String, SimilarityReport, Vec<Fragment>),
+    CorpusMatch(String, String, f32, Vec<Fragment>),
     ZipError,
+    IoError(String),
+    DownloadFailed(String),
     Late,
     EmptyFile(String),
+    DuplicateFile(String),
     NoBeecrowd,
     NoBeecrowdSubmission,
     IncompleteBeecrowdSubmission,
     IncompleteClassroomSubmission,
+    CompileError(String, String),
+    FailedTest(String, usize, Verdict),
 }
 
 impl SubmissionError {
@@ -44,16 +88,90 @@ impl SubmissionError {
             SubmissionError::InvalidSubmission => "INVALID SUBMISSION".to_string(),
             SubmissionError::InvalidZip => "INVALID ZIP".to_string(),
             SubmissionError::InvalidFormat => "INVALID FORMAT".to_string(),
-            SubmissionError::PlagiarismDetected(f1, f2, percentage) => {
-                format!(
+            SubmissionError::PlagiarismDetected(f1, f2, report, fragments) => {
+                let mut message = format!(
                     "PLAGIARISM DETECTED {f1} WITH {f2} ({:.2}%)",
+                    report.score * 100.0
+                );
+
+                if let Some(file) = &report.file {
+                    message.push_str(&format!("\n  FILE METRICS: {}", file.describe()));
+                }
+
+                if let Some(functions) = &report.functions {
+                    message.push_str(&format!(
+                        "\n  FUNCTIONS ({:.2}%): {}",
+                        functions.score * 100.0,
+                        functions
+                            .matches
+                            .iter()
+                            .take(LISTED_FUNCTION_MATCHES)
+                            .map(|m| format!(
+                                "{} AND {} {:.2}%",
+                                m.left,
+                                m.right,
+                                m.metrics.combined * 100.0
+                            ))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ));
+
+                    let hidden = functions
+                        .matches
+                        .len()
+                        .saturating_sub(LISTED_FUNCTION_MATCHES);
+
+                    if hidden > 0 {
+                        message.push_str(&format!(" AND {hidden} MORE"));
+                    }
+
+                    if let Some(closest) = functions.closest() {
+                        message.push_str(&format!(
+                            "\n  CLOSEST FUNCTIONS {} AND {}: {}",
+                            closest.left,
+                            closest.right,
+                            closest.metrics.describe()
+                        ));
+                    }
+                }
+
+                for fr in fragments {
+                    message.push_str(&format!(
+                        "\n  LINES {}-{} OF {f1} MATCH LINES {}-{} OF {f2}",
+                        fr.left.start_line,
+                        fr.left.end_line,
+                        fr.right.start_line,
+                        fr.right.end_line
+                    ));
+                }
+
+                message
+            }
+            SubmissionError::CorpusMatch(f, source, percentage, fragments) => {
+                let mut message = format!(
+                    "PLAGIARISM DETECTED {f} WITH ARCHIVED {source} ({:.2}%)",
                     *percentage * 100.0
-                )
+                );
+
+                for fr in fragments {
+                    message.push_str(&format!(
+                        "\n  LINES {}-{} OF {f} MATCH LINES {}-{} OF {source}",
+                        fr.left.start_line,
+                        fr.left.end_line,
+                        fr.right.start_line,
+                        fr.right.end_line
+                    ));
+                }
+
+                message
             }
             SubmissionError::EmptyFile(f) => {
                 format!("EMPTY FILE {f}")
             }
+            SubmissionError::DuplicateFile(f) => format!("DUPLICATE FILE {f}"),
             SubmissionError::ZipError => "ERROR WHILE EXTRACTING ZIP".to_string(),
+            SubmissionError::IoError(e) => format!("ERROR WHILE COPYING FILES ({e})"),
+            SubmissionError::DownloadFailed(f) => format!("DOWNLOAD FAILED {f}"),
             SubmissionError::Late => "LATE SUBMISSION".to_string(),
             SubmissionError::NoBeecrowd => "NOT LISTED IN BEECROWD CLASS".to_string(),
             SubmissionError::NoBeecrowdSubmission => "NO BEECROWD SUBMISSION".to_string(),
@@ -63,17 +181,63 @@ impl SubmissionError {
             SubmissionError::IncompleteClassroomSubmission => {
                 "INCOMPLETE CLASSROOM SUBMISSION".to_string()
             }
+            SubmissionError::CompileError(f, diagnostics) => {
+                let mut message = format!("COMPILATION ERROR {f}");
+
+                for line in diagnostics.lines().take(5) {
+                    message.push_str(&format!("\n  {line}"));
+                }
+
+                message
+            }
+            SubmissionError::FailedTest(f, case, verdict) => {
+                let verdict = match verdict {
+                    Verdict::Accepted => "ACCEPTED",
+                    Verdict::WrongAnswer => "WRONG ANSWER",
+                    Verdict::RuntimeError => "RUNTIME ERROR",
+                    Verdict::TimeLimitExceeded => "TIME LIMIT EXCEEDED",
+                    Verdict::MemoryLimitExceeded => "MEMORY LIMIT EXCEEDED",
+                    Verdict::OutputLimitExceeded => "OUTPUT LIMIT EXCEEDED",
+                    Verdict::JudgeError => "JUDGE ERROR",
+                };
+
+                format!("{verdict} {f} TEST {case}")
+            }
         }
     }
 }
 
+#[must_use]
+pub fn expected_questions(results: &HashMap<String, SubmissionResult>, rubric: &Rubric) -> i32 {
+    rubric
+        .expected_questions
+        .unwrap_or_else(|| results.values().map(|r| r.solved).max().unwrap_or(0))
+}
+
+pub fn compute_scores(
+    results: &HashMap<String, SubmissionResult>,
+    rubric: &Rubric,
+) -> HashMap<String, ScoreBreakdown> {
+    let expected = expected_questions(results, rubric);
+
+    results
+        .iter()
+        .map(|(email, result)| (email.clone(), score(result, rubric, expected)))
+        .collect()
+}
+
 pub fn generate_report(
     results: HashMap<String, SubmissionResult>,
     path: &str,
+    rubric: &Rubric,
 ) -> Result<HashMap<String, SubmissionResult>, Box<dyn std::error::Error + Send + Sync>> {
     let mut wtr = csv::Writer::from_path(path)?;
 
-    for result in results.values() {
+    let scores = compute_scores(&results, rubric);
+
+    for (email, result) in &results {
+        let breakdown = &scores[email];
+
         let mut comments = result.comments.join(", ");
 
         let errors = result
@@ -91,9 +255,10 @@ pub fn generate_report(
         }
 
         wtr.serialize(Record {
-            name: result.student.profile.name.full_name.clone(),
-            email: result.student.profile.email_address.clone(),
-            score_percent: 1.0,
+            name: result.student.name.clone(),
+            email: result.student.email.clone(),
+            score_percent: (breakdown.score * 10000.0).round() / 100.0,
+            score_breakdown: breakdown.describe(),
             comments,
         })?;
     }
@@ -102,3 +267,53 @@ pub fn generate_report(
 
     Ok(results)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use similarity::{FunctionMatch, FunctionReport, MetricBreakdown};
+
+    #[test]
+    fn plagiarism_comments_list_the_closest_functions_only() {
+        let matches = (0..8)
+            .map(|i| FunctionMatch {
+                left: format!("f{i}"),
+                right: format!("g{i}"),
+                metrics: MetricBreakdown {
+                    levenshtein: Some(1.0 - i as f32 / 10.0),
+                    combined: 1.0 - i as f32 / 10.0,
+                    ..MetricBreakdown::default()
+                },
+            })
+            .collect();
+
+        let report = SimilarityReport {
+            score: 0.9,
+            file: None,
+            functions: Some(FunctionReport {
+                score: 0.8,
+                matches,
+            }),
+        };
+
+        let message = SubmissionError::PlagiarismDetected(
+            "q1_a.c".to_string(),
+            "q1_b.c".to_string(),
+            report,
+            vec![],
+        )
+        .message();
+
+        let lines: Vec<&str> = message.lines().collect();
+        assert_eq!(lines[0], "PLAGIARISM DETECTED q1_a.c WITH q1_b.c (90.00%)");
+        assert_eq!(
+            lines[1],
+            "  FUNCTIONS (80.00%): f0 AND g0 100.00%, f1 AND g1 90.00%, f2 AND g2 80.00%, \
+             f3 AND g3 70.00%, f4 AND g4 60.00% AND 3 MORE"
+        );
+        assert_eq!(
+            lines[2],
+            "  CLOSEST FUNCTIONS f0 AND g0: LEVENSHTEIN 100.00%"
+        );
+    }
+}
@@ -0,0 +1,414 @@
+use serde::Deserialize;
+
+use crate::{SubmissionError, SubmissionResult, Verdict};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rubric {
+    pub expected_questions: Option<i32>,
+    pub tests_weight: f32,
+    pub no_submission: f32,
+    pub invalid_submission: f32,
+    pub invalid_zip: f32,
+    pub invalid_format: f32,
+    pub zip_error: f32,
+    pub io_error: f32,
+    pub download_failed: f32,
+    pub plagiarism: f32,
+    pub corpus_match: f32,
+    pub late: f32,
+    pub empty_file: f32,
+    pub duplicate_file: f32,
+    pub no_beecrowd: f32,
+    pub no_beecrowd_submission: f32,
+    pub incomplete_beecrowd_submission: f32,
+    pub incomplete_classroom_submission: f32,
+    pub compile_error: f32,
+    pub failed_test: f32,
+}
+
+impl Default for Rubric {
+    fn default() -> Self {
+        Self {
+            expected_questions: None,
+            tests_weight: 0.5,
+            no_submission: 1.0,
+            invalid_submission: 1.0,
+            invalid_zip: 1.0,
+            invalid_format: 0.1,
+            zip_error: 1.0,
+            io_error: 0.0,
+            download_failed: 0.0,
+            plagiarism: 1.0,
+            corpus_match: 1.0,
+            late: 0.1,
+            empty_file: 0.0,
+            duplicate_file: 0.0,
+            no_beecrowd: 0.0,
+            no_beecrowd_submission: 0.5,
+            incomplete_beecrowd_submission: 0.1,
+            incomplete_classroom_submission: 0.0,
+            compile_error: 0.0,
+            failed_test: 0.0,
+        }
+    }
+}
+
+impl Rubric {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.expected_questions.is_some_and(|n| n < 1) {
+            return Err("expected_questions must be at least 1".to_string());
+        }
+
+        if !(0.0..=1.0).contains(&self.tests_weight) {
+            return Err("tests_weight must be in [0, 1]".to_string());
+        }
+
+        let penalties = [
+            self.no_submission,
+            self.invalid_submission,
+            self.invalid_zip,
+            self.invalid_format,
+            self.zip_error,
+            self.io_error,
+            self.download_failed,
+            self.plagiarism,
+            self.corpus_match,
+            self.late,
+            self.empty_file,
+            self.duplicate_file,
+            self.no_beecrowd,
+            self.no_beecrowd_submission,
+            self.incomplete_beecrowd_submission,
+            self.incomplete_classroom_submission,
+            self.compile_error,
+            self.failed_test,
+        ];
+
+        if penalties.iter().any(|p| !(0.0..=1.0).contains(p)) {
+            return Err("penalties must be in [0, 1]".to_string());
+        }
+
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn penalty(&self, error: &SubmissionError) -> f32 {
+        match error {
+            SubmissionError::NoSubmission => self.no_submission,
+            SubmissionError::InvalidSubmission => self.invalid_submission,
+            SubmissionError::InvalidZip => self.invalid_zip,
+            SubmissionError::InvalidFormat => self.invalid_format,
+            SubmissionError::PlagiarismDetected(..) => self.plagiarism,
+            SubmissionError::CorpusMatch(..) => self.corpus_match,
+            SubmissionError::ZipError => self.zip_error,
+            SubmissionError::IoError(_) => self.io_error,
+            SubmissionError::DownloadFailed(_) => self.download_failed,
+            SubmissionError::Late => self.late,
+            SubmissionError::EmptyFile(_) => self.empty_file,
+            SubmissionError::DuplicateFile(_) => self.duplicate_file,
+            SubmissionError::NoBeecrowd => self.no_beecrowd,
+            SubmissionError::NoBeecrowdSubmission => self.no_beecrowd_submission,
+            SubmissionError::IncompleteBeecrowdSubmission => self.incomplete_beecrowd_submission,
+            SubmissionError::IncompleteClassroomSubmission => self.incomplete_classroom_submission,
+            SubmissionError::CompileError(..) => self.compile_error,
+            SubmissionError::FailedTest(_, _, Verdict::JudgeError) => 0.0,
+            SubmissionError::FailedTest(..) => self.failed_test,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ScoreBreakdown {
+    pub solved: i32,
+    pub expected: i32,
+    pub completion: f32,
+    pub tests: Option<f32>,
+    pub penalties: Vec<(String, f32)>,
+    pub score: f32,
+}
+
+impl ScoreBreakdown {
+    #[must_use]
+    pub fn describe(&self) -> String {
+        let mut parts = vec![format!(
+            "COMPLETION {}/{} ({:.2}%)",
+            self.solved,
+            self.expected,
+            self.completion * 100.0
+        )];
+
+        if let Some(tests) = self.tests {
+            parts.push(format!("TESTS {:.2}%", tests * 100.0));
+        }
+
+        for (reason, penalty) in &self.penalties {
+            parts.push(format!("{reason} -{:.2}%", penalty * 100.0));
+        }
+
+        parts.push(format!("FINAL {:.2}%", self.score * 100.0));
+
+        parts.join("; ")
+    }
+}
+
+#[must_use]
+pub fn score(result: &SubmissionResult, rubric: &Rubric, expected: i32) -> ScoreBreakdown {
+    let completion = if expected > 0 {
+        result.solved.clamp(0, expected) as f32 / expected as f32
+    } else {
+        1.0
+    };
+
+    let accepted = result
+        .tests
+        .iter()
+        .filter(|t| t.compiled)
+        .flat_map(|t| &t.verdicts)
+        .filter(|v| **v == Verdict::Accepted)
+        .count();
+
+    let total_cases: usize = result
+        .tests
+        .iter()
+        .map(|t| {
+            if t.compiled {
+                t.verdicts
+                    .iter()
+                    .filter(|v| **v != Verdict::JudgeError)
+                    .count()
+            } else {
+                1
+            }
+        })
+        .sum();
+
+    let tests = (total_cases > 0).then(|| accepted as f32 / total_cases as f32);
+
+    let mut base = match tests {
+        Some(t) => (1.0 - rubric.tests_weight) * completion + rubric.tests_weight * t,
+        None => completion,
+    };
+
+    // Each kind of error is charged once, however many files or cases it
+    // covers; the question count and test ratio already scale with those.
+    let mut charged = Vec::new();
+    let mut penalties: Vec<(String, f32)> = Vec::new();
+
+    for error in &result.errors {
+        let penalty = rubric.penalty(error);
+        let kind = std::mem::discriminant(error);
+
+        if penalty > 0.0 && !charged.contains(&kind) {
+            charged.push(kind);
+            penalties.push((error.message(), penalty));
+        }
+    }
+
+    for (_, penalty) in &penalties {
+        base -= penalty;
+    }
+
+    ScoreBreakdown {
+        solved: result.solved,
+        expected,
+        completion,
+        tests,
+        penalties: penalties
+            .into_iter()
+            .map(|(m, p)| (m.lines().next().unwrap_or_default().to_string(), p))
+            .collect(),
+        score: base.clamp(0.0, 1.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Student, TestReport};
+
+    fn result(
+        solved: i32,
+        errors: Vec<SubmissionError>,
+        tests: Vec<TestReport>,
+    ) -> SubmissionResult {
+        SubmissionResult {
+            student: Student {
+                id: "1".to_string(),
+                email: "a@b.c".to_string(),
+                name: "A".to_string(),
+            },
+            comments: Vec::new(),
+            errors,
+            solved,
+            tests,
+        }
+    }
+
+    fn compiled(file: &str, verdicts: Vec<Verdict>) -> TestReport {
+        TestReport {
+            file: file.to_string(),
+            compiled: true,
+            diagnostics: None,
+            verdicts,
+        }
+    }
+
+    fn uncompiled(file: &str) -> TestReport {
+        TestReport {
+            file: file.to_string(),
+            compiled: false,
+            diagnostics: Some("error".to_string()),
+            verdicts: Vec::new(),
+        }
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-6, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn completion_is_solved_over_expected() {
+        let rubric = Rubric::default();
+
+        let breakdown = score(&result(3, Vec::new(), Vec::new()), &rubric, 4);
+        assert_close(breakdown.completion, 0.75);
+        assert_close(breakdown.score, 0.75);
+        assert_eq!(breakdown.tests, None);
+
+        let breakdown = score(&result(6, Vec::new(), Vec::new()), &rubric, 4);
+        assert_close(breakdown.completion, 1.0);
+        assert_close(breakdown.score, 1.0);
+
+        let breakdown = score(&result(0, Vec::new(), Vec::new()), &rubric, 0);
+        assert_close(breakdown.completion, 1.0);
+    }
+
+    #[test]
+    fn tests_are_blended_by_weight() {
+        let rubric = Rubric {
+            tests_weight: 0.25,
+            ..Rubric::default()
+        };
+
+        let tests = vec![
+            compiled(
+                "q1.c",
+                vec![Verdict::Accepted, Verdict::WrongAnswer, Verdict::JudgeError],
+            ),
+            uncompiled("q2.c"),
+            compiled("q3.c", vec![Verdict::Accepted, Verdict::TimeLimitExceeded]),
+        ];
+
+        // 2 accepted out of 2 + 1 + 2 cases: the judge error is left out and
+        // the file that did not compile counts as a single failed case.
+        let breakdown = score(&result(2, Vec::new(), tests), &rubric, 4);
+        assert_close(breakdown.tests.unwrap(), 0.4);
+        assert_close(breakdown.score, 0.75 * 0.5 + 0.25 * 0.4);
+    }
+
+    #[test]
+    fn only_judge_errors_give_no_test_score() {
+        let tests = vec![compiled("q1.c", vec![Verdict::JudgeError])];
+
+        let breakdown = score(&result(1, Vec::new(), tests), &Rubric::default(), 2);
+        assert_eq!(breakdown.tests, None);
+        assert_close(breakdown.score, 0.5);
+    }
+
+    #[test]
+    fn penalties_follow_the_rubric() {
+        let rubric = Rubric {
+            late: 0.2,
+            empty_file: 0.1,
+            failed_test: 0.3,
+            ..Rubric::default()
+        };
+
+        let errors = vec![
+            SubmissionError::Late,
+            SubmissionError::EmptyFile("q1.c".to_string()),
+            SubmissionError::FailedTest("q2.c".to_string(), 1, Verdict::JudgeError),
+            SubmissionError::DuplicateFile("q3.c".to_string()),
+        ];
+
+        let breakdown = score(&result(4, errors, Vec::new()), &rubric, 4);
+        assert_eq!(
+            breakdown.penalties,
+            vec![
+                ("LATE SUBMISSION".to_string(), 0.2),
+                ("EMPTY FILE q1.c".to_string(), 0.1),
+            ]
+        );
+        assert_close(breakdown.score, 0.7);
+    }
+
+    #[test]
+    fn penalties_are_charged_once_per_kind() {
+        let rubric = Rubric {
+            failed_test: 0.2,
+            empty_file: 0.1,
+            ..Rubric::default()
+        };
+
+        let errors = vec![
+            SubmissionError::FailedTest("q1.c".to_string(), 1, Verdict::WrongAnswer),
+            SubmissionError::FailedTest("q1.c".to_string(), 2, Verdict::RuntimeError),
+            SubmissionError::FailedTest("q2.c".to_string(), 1, Verdict::WrongAnswer),
+            SubmissionError::EmptyFile("q3.c".to_string()),
+            SubmissionError::EmptyFile("q4.c".to_string()),
+        ];
+
+        let breakdown = score(&result(4, errors, Vec::new()), &rubric, 4);
+        assert_eq!(breakdown.penalties.len(), 2);
+        assert_close(breakdown.score, 0.7);
+    }
+
+    #[test]
+    fn score_is_clamped() {
+        let rubric = Rubric {
+            late: 0.6,
+            invalid_format: 0.6,
+            ..Rubric::default()
+        };
+
+        let errors = vec![SubmissionError::Late, SubmissionError::InvalidFormat];
+
+        let breakdown = score(&result(1, errors, Vec::new()), &rubric, 2);
+        assert_close(breakdown.score, 0.0);
+
+        let breakdown = score(&result(5, Vec::new(), Vec::new()), &rubric, 2);
+        assert_close(breakdown.score, 1.0);
+    }
+
+    #[test]
+    fn default_rubric_is_valid() {
+        assert!(Rubric::default().validate().is_ok());
+    }
+
+    #[test]
+    fn invalid_rubrics_are_rejected() {
+        let rubrics = [
+            Rubric {
+                late: -0.1,
+                ..Rubric::default()
+            },
+            Rubric {
+                plagiarism: f32::NAN,
+                ..Rubric::default()
+            },
+            Rubric {
+                tests_weight: 1.5,
+                ..Rubric::default()
+            },
+            Rubric {
+                expected_questions: Some(0),
+                ..Rubric::default()
+            },
+        ];
+
+        for rubric in rubrics {
+            assert!(rubric.validate().is_err(), "{rubric:?}");
+        }
+    }
+}
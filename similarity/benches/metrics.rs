@@ -0,0 +1,177 @@
+use std::collections::{HashMap, HashSet};
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use similarity::{
+    Token, analyze_code, compare_two_codes_cached,
+    config::TokenWeights,
+    metrics::{lcs_similarity, levenshtein_similarity, weighted_cosine},
+};
+
+const LINES: [usize; 2] = [250, 1000];
+
+fn synthetic_code(lines: usize, seed: u64) -> String {
+    let mut state = seed;
+    let mut next = move |n: u64| {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (state >> 33) % n
+    };
+
+    let mut code =
+        String::from("#include <stdio.h>\n\nint main() {\n    int a = 0, b = 1, c = 2;\n");
+
+    while code.lines().count() < lines {
+        let line = match next(6) {
+            0 => format!("    a = b + {} * c;\n", next(100)),
+            1 => format!("    if (a > {}) {{ b = b - a; }}\n", next(100)),
+            2 => format!(
+                "    for (int i = 0; i < {}; i++) {{ c += i; }}\n",
+                next(100)
+            ),
+            3 => "    while (b < c && a != 0) { b++; }\n".to_string(),
+            4 => format!("    printf(\"%d\\n\", a * {});\n", next(10)),
+            _ => format!("    c = (a + b) / {}.5;\n", next(10) + 1),
+        };
+        code.push_str(&line);
+    }
+
+    code.push_str("    return 0;\n}\n");
+    code
+}
+
+fn tokens(lines: usize, seed: u64) -> Vec<Token> {
+    analyze_code(&synthetic_code(lines, seed))
+        .unwrap()
+        .file_tokens
+}
+
+fn names(tokens: &[Token]) -> String {
+    tokens
+        .iter()
+        .map(|t| format!("{t:?}").split('(').next().unwrap().to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn quadratic_lcs(a: &[Token], b: &[Token]) -> f32 {
+    let (m, n) = (a.len(), b.len());
+    let mut dp = vec![vec![0; n + 1]; m + 1];
+
+    for i in 1..=m {
+        for j in 1..=n {
+            dp[i][j] = if a[i - 1] == b[j - 1] {
+                dp[i - 1][j - 1] + 1
+            } else {
+                dp[i - 1][j].max(dp[i][j - 1])
+            };
+        }
+    }
+
+    dp[m][n] as f32 / ((m + n) as f32 / 2.0)
+}
+
+fn string_levenshtein(a: &[Token], b: &[Token]) -> f32 {
+    strsim::normalized_levenshtein(&names(a), &names(b)) as f32
+}
+
+fn token_weight(token: &Token) -> f32 {
+    match token {
+        Token::If | Token::Loop => 2.0,
+        Token::Func => 1.5,
+        Token::Var | Token::ConstInt | Token::ConstFloat | Token::ConstStr => 0.5,
+        _ => 1.0,
+    }
+}
+
+fn rescanning_cosine(tokens1: &[Token], tokens2: &[Token]) -> f32 {
+    let all_tokens: HashSet<_> = tokens1.iter().chain(tokens2.iter()).collect();
+    let mut w = HashMap::new();
+
+    for token in &all_tokens {
+        let count1 = tokens1.iter().filter(|t| t == token).count() as f32;
+        let count2 = tokens2.iter().filter(|t| t == token).count() as f32;
+        w.insert(
+            *token,
+            (count1 * token_weight(token), count2 * token_weight(token)),
+        );
+    }
+
+    let (dot, norm1, norm2) = w.values().fold((0.0, 0.0, 0.0), |(d, n1, n2), (v1, v2)| {
+        (d + v1 * v2, n1 + v1 * v1, n2 + v2 * v2)
+    });
+
+    if norm1 == 0.0 || norm2 == 0.0 {
+        0.0
+    } else {
+        dot / (f32::sqrt(norm1) * f32::sqrt(norm2))
+    }
+}
+
+fn assert_same(metric: &str, old: f32, new: f32) {
+    assert!(
+        (old - new).abs() < 1e-5,
+        "{metric} changed: {old} before, {new} after"
+    );
+}
+
+fn bench_metrics(c: &mut Criterion) {
+    for lines in LINES {
+        let (a, b) = (tokens(lines, 1), tokens(lines, 2));
+
+        assert_same("lcs", quadratic_lcs(&a, &b), lcs_similarity(&a, &b));
+        assert_same(
+            "levenshtein",
+            string_levenshtein(&a, &b),
+            levenshtein_similarity(&a, &b),
+        );
+        assert_same(
+            "cosine",
+            rescanning_cosine(&a, &b),
+            weighted_cosine(&a, &b, &TokenWeights::default()),
+        );
+
+        let mut group = c.benchmark_group(format!("metrics/{lines}_lines"));
+        group.sample_size(10);
+
+        group.bench_function(BenchmarkId::new("lcs", "table"), |bench| {
+            bench.iter(|| quadratic_lcs(&a, &b))
+        });
+        group.bench_function(BenchmarkId::new("lcs", "bit_parallel"), |bench| {
+            bench.iter(|| lcs_similarity(&a, &b))
+        });
+        group.bench_function(BenchmarkId::new("levenshtein", "strsim"), |bench| {
+            bench.iter(|| string_levenshtein(&a, &b))
+        });
+        group.bench_function(BenchmarkId::new("levenshtein", "bit_parallel"), |bench| {
+            bench.iter(|| levenshtein_similarity(&a, &b))
+        });
+        group.bench_function(BenchmarkId::new("cosine", "rescan"), |bench| {
+            bench.iter(|| rescanning_cosine(&a, &b))
+        });
+        group.bench_function(BenchmarkId::new("cosine", "counts"), |bench| {
+            bench.iter(|| weighted_cosine(&a, &b, &TokenWeights::default()))
+        });
+
+        group.finish();
+    }
+}
+
+fn bench_compare(c: &mut Criterion) {
+    let mut group = c.benchmark_group("compare");
+    group.sample_size(10);
+
+    for lines in LINES {
+        let a = analyze_code(&synthetic_code(lines, 1)).unwrap();
+        let b = analyze_code(&synthetic_code(lines, 2)).unwrap();
+
+        group.bench_function(BenchmarkId::from_parameter(lines), |bench| {
+            bench.iter(|| compare_two_codes_cached(&a, &b))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_metrics, bench_compare);
+criterion_main!(benches);
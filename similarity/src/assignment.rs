@@ -0,0 +1,162 @@
+pub fn max_weight_assignment(weights: &[Vec<f32>]) -> Vec<(usize, usize)> {
+    let rows = weights.len();
+    let cols = weights.first().map_or(0, Vec::len);
+
+    if rows == 0 || cols == 0 {
+        return Vec::new();
+    }
+
+    if rows > cols {
+        let transposed: Vec<Vec<f32>> = (0..cols)
+            .map(|j| (0..rows).map(|i| weights[i][j]).collect())
+            .collect();
+
+        return max_weight_assignment(&transposed)
+            .into_iter()
+            .map(|(j, i)| (i, j))
+            .collect();
+    }
+
+    let cost = |i: usize, j: usize| -f64::from(weights[i - 1][j - 1]);
+
+    let mut u = vec![0.0; rows + 1];
+    let mut v = vec![0.0; cols + 1];
+    let mut owner = vec![0usize; cols + 1];
+    let mut way = vec![0usize; cols + 1];
+
+    for i in 1..=rows {
+        owner[0] = i;
+        let mut j0 = 0;
+        let mut minv = vec![f64::INFINITY; cols + 1];
+        let mut used = vec![false; cols + 1];
+
+        loop {
+            used[j0] = true;
+            let i0 = owner[j0];
+            let mut delta = f64::INFINITY;
+            let mut j1 = 0;
+
+            for j in 1..=cols {
+                if used[j] {
+                    continue;
+                }
+
+                let reduced = cost(i0, j) - u[i0] - v[j];
+
+                if reduced < minv[j] {
+                    minv[j] = reduced;
+                    way[j] = j0;
+                }
+
+                if minv[j] < delta {
+                    delta = minv[j];
+                    j1 = j;
+                }
+            }
+
+            for j in 0..=cols {
+                if used[j] {
+                    u[owner[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    minv[j] -= delta;
+                }
+            }
+
+            j0 = j1;
+
+            if owner[j0] == 0 {
+                break;
+            }
+        }
+
+        while j0 != 0 {
+            let j1 = way[j0];
+            owner[j0] = owner[j1];
+            j0 = j1;
+        }
+    }
+
+    let mut pairs: Vec<(usize, usize)> = (1..=cols)
+        .filter(|&j| owner[j] != 0)
+        .map(|j| (owner[j] - 1, j - 1))
+        .collect();
+
+    pairs.sort_unstable();
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Lcg;
+
+    fn total(weights: &[Vec<f32>], pairs: &[(usize, usize)]) -> f32 {
+        pairs.iter().map(|&(i, j)| weights[i][j]).sum()
+    }
+
+    fn brute_force(weights: &[Vec<f32>], row: usize, used: &mut Vec<bool>) -> f32 {
+        if row == weights.len() {
+            return 0.0;
+        }
+
+        let mut best = if weights.len() - row > used.iter().filter(|u| !**u).count() {
+            brute_force(weights, row + 1, used)
+        } else {
+            f32::NEG_INFINITY
+        };
+
+        for j in 0..used.len() {
+            if !used[j] {
+                used[j] = true;
+                best = best.max(weights[row][j] + brute_force(weights, row + 1, used));
+                used[j] = false;
+            }
+        }
+
+        best
+    }
+
+    #[test]
+    fn matches_brute_force_optimum() {
+        let mut rng = Lcg::new(19);
+
+        for _ in 0..300 {
+            let (rows, cols) = (1 + rng.below(6), 1 + rng.below(6));
+            let weights: Vec<Vec<f32>> = (0..rows)
+                .map(|_| (0..cols).map(|_| rng.below(1000) as f32 / 1000.0).collect())
+                .collect();
+
+            let pairs = max_weight_assignment(&weights);
+
+            assert_eq!(pairs.len(), rows.min(cols));
+
+            let mut seen_rows: Vec<usize> = pairs.iter().map(|p| p.0).collect();
+            let mut seen_cols: Vec<usize> = pairs.iter().map(|p| p.1).collect();
+            seen_rows.dedup();
+            seen_cols.sort_unstable();
+            seen_cols.dedup();
+            assert_eq!(seen_rows.len(), pairs.len());
+            assert_eq!(seen_cols.len(), pairs.len());
+
+            let expected = brute_force(&weights, 0, &mut vec![false; cols]);
+            assert!(
+                (total(&weights, &pairs) - expected).abs() < 1e-4,
+                "{weights:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn prefers_one_to_one_over_greedy_best() {
+        let weights = vec![vec![0.9, 0.8], vec![0.85, 0.1]];
+
+        assert_eq!(max_weight_assignment(&weights), vec![(0, 1), (1, 0)]);
+    }
+
+    #[test]
+    fn empty_matrices_have_no_pairs() {
+        assert!(max_weight_assignment(&[]).is_empty());
+        assert!(max_weight_assignment(&[vec![], vec![]]).is_empty());
+    }
+}
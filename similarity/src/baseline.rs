@@ -0,0 +1,206 @@
+use std::collections::HashSet;
+
+use crate::{
+    AnalyzedFile, config::WinnowingConfig, matching::greedy_string_tiling, winnowing::fingerprint,
+};
+
+const MIN_BASELINE_MATCH: usize = 5;
+
+pub fn subtract_baseline(
+    analyzed: &AnalyzedFile,
+    baselines: &[AnalyzedFile],
+    winnowing: &WinnowingConfig,
+) -> AnalyzedFile {
+    if baselines.is_empty() || analyzed.file_tokens.is_empty() {
+        return analyzed.clone();
+    }
+
+    let min_match = winnowing.kgram.max(MIN_BASELINE_MATCH);
+    let mut covered = vec![false; analyzed.file_tokens.len()];
+
+    for baseline in baselines {
+        for (i, _, len) in
+            greedy_string_tiling(&analyzed.file_tokens, &baseline.file_tokens, min_match)
+        {
+            covered[i..i + len].fill(true);
+        }
+    }
+
+    // Position of each token once the covered ones are dropped.
+    let mut kept = 0;
+    let positions: Vec<usize> = covered
+        .iter()
+        .map(|c| {
+            let position = kept;
+            kept += usize::from(!c);
+            position
+        })
+        .collect();
+
+    let mut removed_at: Vec<usize> = covered
+        .windows(2)
+        .enumerate()
+        .filter(|(_, w)| w[0] && !w[1])
+        .map(|(i, _)| positions[i + 1])
+        .filter(|p| *p > 0)
+        .collect();
+    removed_at.dedup();
+
+    let (file_tokens, file_spans): (Vec<_>, Vec<_>) = analyzed
+        .file_tokens
+        .iter()
+        .zip(&analyzed.file_spans)
+        .zip(&covered)
+        .filter(|(_, c)| !**c)
+        .map(|((t, s), _)| (t.clone(), *s))
+        .unzip();
+
+    let baseline_hashes: HashSet<u64> = baselines
+        .iter()
+        .flat_map(|b| fingerprint(&b.file_tokens, winnowing))
+        .map(|f| f.hash)
+        .collect();
+
+    let fingerprints = fingerprint(&analyzed.file_tokens, winnowing)
+        .into_iter()
+        .filter(|f| {
+            let end = (f.position + winnowing.kgram).min(covered.len());
+            !covered[f.position..end].contains(&true)
+        })
+        .filter(|f| !baseline_hashes.contains(&f.hash))
+        .map(|mut f| {
+            f.position = positions[f.position];
+            f
+        })
+        .collect();
+
+    let functions = analyzed
+        .functions
+        .iter()
+        .filter(|(_, tokens)| {
+            !baselines
+                .iter()
+                .any(|b| b.functions.values().any(|bt| bt == *tokens))
+        })
+        .map(|(name, tokens)| (name.clone(), tokens.clone()))
+        .collect();
+
+    let removed = covered.iter().filter(|c| **c).count();
+
+    AnalyzedFile {
+        file_tokens,
+        file_spans,
+        functions,
+        fingerprints,
+        discounted: removed as f32 / analyzed.file_tokens.len() as f32,
+        language: analyzed.language,
+        removed_at,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{Language, Span, Token, matching::tiling_within, test_support::Lcg};
+
+    fn file(tokens: Vec<Token>, winnowing: &WinnowingConfig) -> AnalyzedFile {
+        AnalyzedFile {
+            file_spans: vec![
+                Span {
+                    start_byte: 0,
+                    end_byte: 0,
+                    start_line: 0,
+                    end_line: 0,
+                };
+                tokens.len()
+            ],
+            fingerprints: fingerprint(&tokens, winnowing),
+            file_tokens: tokens,
+            functions: HashMap::new(),
+            discounted: 0.0,
+            language: Language::C,
+            removed_at: vec![],
+        }
+    }
+
+    #[test]
+    fn fingerprints_never_span_removed_code() {
+        let winnowing = WinnowingConfig::default();
+        let mut rng = Lcg::new(5);
+        let before = rng.tokens(40, 50);
+        let template = rng.tokens(60, 50);
+        let after = rng.tokens(40, 50);
+
+        let submission = file(
+            [before.clone(), template.clone(), after.clone()].concat(),
+            &winnowing,
+        );
+        let subtracted = subtract_baseline(&submission, &[file(template, &winnowing)], &winnowing);
+
+        assert_eq!(subtracted.file_tokens, [before.clone(), after].concat());
+        assert_eq!(subtracted.removed_at, [before.len()]);
+        assert!(!subtracted.fingerprints.is_empty());
+
+        let every_kgram = WinnowingConfig {
+            window: 1,
+            ..winnowing.clone()
+        };
+        let kgrams: HashSet<_> = fingerprint(&subtracted.file_tokens, &every_kgram)
+            .into_iter()
+            .collect();
+
+        for f in &subtracted.fingerprints {
+            let end = f.position + winnowing.kgram;
+            assert!(end <= before.len() || f.position >= before.len());
+            assert!(kgrams.contains(f), "{f:?} does not index file_tokens");
+        }
+    }
+
+    #[test]
+    fn fragments_never_cross_removed_code() {
+        let winnowing = WinnowingConfig::default();
+        let mut rng = Lcg::new(11);
+        let template = rng.tokens(60, 50);
+        let left = rng.tokens(20, 50);
+        let right = rng.tokens(20, 50);
+
+        let submission = [left.clone(), template.clone(), right.clone()].concat();
+        // Joined without the template, the two halves would tile as one.
+        let other = [left.clone(), right].concat();
+
+        let baseline = [file(template, &winnowing)];
+        let subtracted = subtract_baseline(&file(submission, &winnowing), &baseline, &winnowing);
+        let other = subtract_baseline(&file(other, &winnowing), &baseline, &winnowing);
+
+        let tiles = tiling_within(
+            &subtracted.file_tokens,
+            &subtracted.removed_at,
+            &other.file_tokens,
+            &other.removed_at,
+            15,
+        );
+
+        assert_eq!(
+            greedy_string_tiling(&subtracted.file_tokens, &other.file_tokens, 15),
+            [(0, 0, 40)]
+        );
+        assert_eq!(tiles, [(0, 0, 20), (20, 20, 20)]);
+    }
+
+    #[test]
+    fn runs_shorter_than_a_kgram_are_kept() {
+        let winnowing = WinnowingConfig::default();
+        let mut rng = Lcg::new(9);
+        let shared = rng.tokens(winnowing.kgram - 1, 50);
+        let baseline = [vec![Token::Var; 30], shared.clone(), vec![Token::Var; 30]].concat();
+        let submission = [vec![Token::Func; 30], shared, vec![Token::Func; 30]].concat();
+
+        let submission = file(submission, &winnowing);
+        let subtracted = subtract_baseline(&submission, &[file(baseline, &winnowing)], &winnowing);
+
+        assert_eq!(subtracted.file_tokens, submission.file_tokens);
+        assert_eq!(subtracted.discounted, 0.0);
+    }
+}
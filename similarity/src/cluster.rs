@@ -0,0 +1,158 @@
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Debug, Clone)]
+pub struct Cluster {
+    pub members: Vec<usize>,
+    pub average: f32,
+    pub central: usize,
+}
+
+fn find(parent: &mut HashMap<usize, usize>, node: usize) -> usize {
+    let mut root = node;
+
+    while parent[&root] != root {
+        root = parent[&root];
+    }
+
+    let mut current = node;
+
+    while parent[&current] != root {
+        let next = parent[&current];
+        parent.insert(current, root);
+        current = next;
+    }
+
+    root
+}
+
+pub fn clusters(edges: &[(usize, usize, f32)]) -> Vec<Cluster> {
+    let mut parent: HashMap<usize, usize> = HashMap::new();
+
+    for &(a, b, _) in edges {
+        parent.entry(a).or_insert(a);
+        parent.entry(b).or_insert(b);
+
+        let (ra, rb) = (find(&mut parent, a), find(&mut parent, b));
+
+        if ra != rb {
+            parent.insert(ra.max(rb), ra.min(rb));
+        }
+    }
+
+    let mut components: BTreeMap<usize, Vec<(usize, usize, f32)>> = BTreeMap::new();
+
+    for &(a, b, score) in edges {
+        let root = find(&mut parent, a);
+        components.entry(root).or_default().push((a, b, score));
+    }
+
+    let mut clusters: Vec<Cluster> = components
+        .into_values()
+        .map(|edges| {
+            let mut strength: BTreeMap<usize, f32> = BTreeMap::new();
+
+            for &(a, b, score) in &edges {
+                *strength.entry(a).or_default() += score;
+                *strength.entry(b).or_default() += score;
+            }
+
+            let central = strength
+                .iter()
+                .max_by(|x, y| x.1.partial_cmp(y.1).unwrap().then(y.0.cmp(x.0)))
+                .map(|(id, _)| *id)
+                .unwrap();
+
+            Cluster {
+                members: strength.into_keys().collect(),
+                average: edges.iter().map(|e| e.2).sum::<f32>() / edges.len() as f32,
+                central,
+            }
+        })
+        .collect();
+
+    clusters.sort_by(|a, b| {
+        b.members
+            .len()
+            .cmp(&a.members.len())
+            .then(b.average.partial_cmp(&a.average).unwrap())
+    });
+
+    clusters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Lcg;
+    use std::collections::BTreeSet;
+
+    fn naive_components(edges: &[(usize, usize, f32)]) -> BTreeSet<Vec<usize>> {
+        let mut groups: Vec<BTreeSet<usize>> = vec![];
+
+        for &(a, b, _) in edges {
+            let (joined, rest): (Vec<_>, Vec<_>) = groups
+                .into_iter()
+                .partition(|g| g.contains(&a) || g.contains(&b));
+
+            let mut merged: BTreeSet<usize> = joined.into_iter().flatten().collect();
+            merged.extend([a, b]);
+
+            groups = rest;
+            groups.push(merged);
+        }
+
+        groups
+            .into_iter()
+            .map(|g| g.into_iter().collect())
+            .collect()
+    }
+
+    #[test]
+    fn members_match_connected_components() {
+        let mut rng = Lcg::new(13);
+
+        for _ in 0..100 {
+            let nodes = 2 + rng.below(30);
+            let edges: Vec<(usize, usize, f32)> = (0..rng.below(25))
+                .map(|_| {
+                    (
+                        rng.below(nodes),
+                        rng.below(nodes),
+                        rng.below(100) as f32 / 100.0,
+                    )
+                })
+                .filter(|(a, b, _)| a != b)
+                .collect();
+
+            let found: BTreeSet<Vec<usize>> =
+                clusters(&edges).into_iter().map(|c| c.members).collect();
+
+            assert_eq!(found, naive_components(&edges), "{edges:?}");
+        }
+    }
+
+    #[test]
+    fn reports_average_and_most_connected_member() {
+        let edges = [
+            (1, 2, 0.9),
+            (2, 3, 0.8),
+            (2, 4, 0.7),
+            (7, 8, 0.95),
+            (3, 4, 0.6),
+        ];
+
+        let found = clusters(&edges);
+
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].members, vec![1, 2, 3, 4]);
+        assert_eq!(found[0].central, 2);
+        assert!((found[0].average - 0.75).abs() < 1e-6);
+        assert_eq!(found[1].members, vec![7, 8]);
+        assert_eq!(found[1].central, 7);
+    }
+
+    #[test]
+    fn no_edges_means_no_clusters() {
+        assert!(clusters(&[]).is_empty());
+    }
+}
@@ -0,0 +1,170 @@
+use serde::{Deserialize, Serialize};
+
+use crate::Token;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetricWeights {
+    pub levenshtein: f32,
+    pub jaccard: f32,
+    pub lcs: f32,
+    pub cosine: f32,
+}
+
+impl Default for MetricWeights {
+    fn default() -> Self {
+        Self {
+            levenshtein: 0.4,
+            jaccard: 0.2,
+            lcs: 0.2,
+            cosine: 0.2,
+        }
+    }
+}
+
+impl MetricWeights {
+    #[must_use]
+    pub fn total(&self) -> f32 {
+        self.levenshtein + self.jaccard + self.lcs + self.cosine
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TokenWeights {
+    pub var: f32,
+    pub func: f32,
+    pub const_int: f32,
+    pub const_float: f32,
+    pub const_str: f32,
+    pub r#loop: f32,
+    pub r#if: f32,
+    pub arith_op: f32,
+    pub logic_op: f32,
+    pub rel_op: f32,
+    pub other: f32,
+}
+
+impl Default for TokenWeights {
+    fn default() -> Self {
+        Self {
+            var: 0.5,
+            func: 1.5,
+            const_int: 0.5,
+            const_float: 0.5,
+            const_str: 0.5,
+            r#loop: 2.0,
+            r#if: 2.0,
+            arith_op: 1.0,
+            logic_op: 1.0,
+            rel_op: 1.0,
+            other: 1.0,
+        }
+    }
+}
+
+impl TokenWeights {
+    #[must_use]
+    pub fn weight(&self, token: &Token) -> f32 {
+        match token {
+            Token::Var => self.var,
+            Token::Func => self.func,
+            Token::ConstInt => self.const_int,
+            Token::ConstFloat => self.const_float,
+            Token::ConstStr => self.const_str,
+            Token::Loop => self.r#loop,
+            Token::If => self.r#if,
+            Token::ArithOp => self.arith_op,
+            Token::LogicOp => self.logic_op,
+            Token::RelOp => self.rel_op,
+            Token::Other(_) => self.other,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WinnowingConfig {
+    pub kgram: usize,
+    pub window: usize,
+    pub min_shared: usize,
+    /// Fingerprints found in more than this share of the files are skipped when choosing
+    /// candidate pairs, unless at most `MIN_PRUNED_POSTINGS` files share them. Lower values
+    /// hide more boilerplate but can also hide a large group of students copying the same
+    /// solution; starter code is better removed with a baseline.
+    pub max_document_frequency: f32,
+}
+
+impl Default for WinnowingConfig {
+    fn default() -> Self {
+        Self {
+            kgram: 12,
+            window: 8,
+            min_shared: 2,
+            max_document_frequency: 0.9,
+        }
+    }
+}
+
+impl WinnowingConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.kgram == 0 || self.window == 0 || self.min_shared == 0 {
+            return Err("kgram, window and min_shared must be at least 1".to_string());
+        }
+
+        if !(self.max_document_frequency > 0.0 && self.max_document_frequency <= 1.0) {
+            return Err("max_document_frequency must be in (0, 1]".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SimilarityConfig {
+    pub metrics: MetricWeights,
+    pub tokens: TokenWeights,
+    pub file_weight: f32,
+    pub function_weight: f32,
+    pub min_function_tokens: usize,
+}
+
+impl Default for SimilarityConfig {
+    fn default() -> Self {
+        Self {
+            metrics: MetricWeights::default(),
+            tokens: TokenWeights::default(),
+            file_weight: 0.5,
+            function_weight: 0.5,
+            min_function_tokens: 0,
+        }
+    }
+}
+
+impl SimilarityConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        let weights = [
+            self.metrics.levenshtein,
+            self.metrics.jaccard,
+            self.metrics.lcs,
+            self.metrics.cosine,
+            self.file_weight,
+            self.function_weight,
+        ];
+
+        if weights.iter().any(|w| !w.is_finite() || *w < 0.0) {
+            return Err("similarity weights must be non-negative numbers".to_string());
+        }
+
+        if self.metrics.total() == 0.0 {
+            return Err("at least one similarity metric must have a positive weight".to_string());
+        }
+
+        if self.file_weight + self.function_weight == 0.0 {
+            return Err("file_weight and function_weight cannot both be zero".to_string());
+        }
+
+        Ok(())
+    }
+}
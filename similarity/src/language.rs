@@ -0,0 +1,256 @@
+use serde::{Deserialize, Serialize};
+use std::{path::Path, str::FromStr};
+
+use crate::Token;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Language {
+    #[default]
+    C,
+    Cpp,
+    Java,
+    Python,
+}
+
+pub(crate) struct Grammar {
+    pub tokens: &'static [(&'static str, Token)],
+    pub numbers: &'static [&'static str],
+    pub operators: &'static [&'static str],
+    pub functions: &'static [&'static str],
+}
+
+const C_GRAMMAR: Grammar = Grammar {
+    tokens: &[
+        ("identifier", Token::Var),
+        ("function_definition", Token::Func),
+        ("call_expression", Token::Func),
+        ("char_literal", Token::ConstInt),
+        ("string_literal", Token::ConstStr),
+        ("for_statement", Token::Loop),
+        ("while_statement", Token::Loop),
+        ("do_statement", Token::Loop),
+        ("if_statement", Token::If),
+    ],
+    numbers: &["number_literal"],
+    operators: &["binary_expression"],
+    functions: &["function_definition"],
+};
+
+const CPP_GRAMMAR: Grammar = Grammar {
+    tokens: &[
+        ("identifier", Token::Var),
+        ("field_identifier", Token::Var),
+        ("function_definition", Token::Func),
+        ("lambda_expression", Token::Func),
+        ("call_expression", Token::Func),
+        ("char_literal", Token::ConstInt),
+        ("string_literal", Token::ConstStr),
+        ("raw_string_literal", Token::ConstStr),
+        ("for_statement", Token::Loop),
+        ("for_range_loop", Token::Loop),
+        ("while_statement", Token::Loop),
+        ("do_statement", Token::Loop),
+        ("if_statement", Token::If),
+    ],
+    numbers: &["number_literal"],
+    operators: &["binary_expression"],
+    functions: &["function_definition"],
+};
+
+const JAVA_GRAMMAR: Grammar = Grammar {
+    tokens: &[
+        ("identifier", Token::Var),
+        ("method_declaration", Token::Func),
+        ("constructor_declaration", Token::Func),
+        ("lambda_expression", Token::Func),
+        ("method_invocation", Token::Func),
+        ("decimal_integer_literal", Token::ConstInt),
+        ("hex_integer_literal", Token::ConstInt),
+        ("octal_integer_literal", Token::ConstInt),
+        ("binary_integer_literal", Token::ConstInt),
+        ("character_literal", Token::ConstInt),
+        ("decimal_floating_point_literal", Token::ConstFloat),
+        ("hex_floating_point_literal", Token::ConstFloat),
+        ("string_literal", Token::ConstStr),
+        ("for_statement", Token::Loop),
+        ("enhanced_for_statement", Token::Loop),
+        ("while_statement", Token::Loop),
+        ("do_statement", Token::Loop),
+        ("if_statement", Token::If),
+    ],
+    numbers: &[],
+    operators: &["binary_expression"],
+    functions: &["method_declaration", "constructor_declaration"],
+};
+
+const PYTHON_GRAMMAR: Grammar = Grammar {
+    tokens: &[
+        ("identifier", Token::Var),
+        ("function_definition", Token::Func),
+        ("lambda", Token::Func),
+        ("call", Token::Func),
+        ("integer", Token::ConstInt),
+        ("float", Token::ConstFloat),
+        ("string", Token::ConstStr),
+        ("for_statement", Token::Loop),
+        ("while_statement", Token::Loop),
+        ("if_statement", Token::If),
+        ("comparison_operator", Token::RelOp),
+    ],
+    numbers: &[],
+    operators: &["binary_operator", "boolean_operator"],
+    functions: &["function_definition"],
+};
+
+impl Language {
+    pub const ALL: [Language; 4] = [Language::C, Language::Cpp, Language::Java, Language::Python];
+
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::C => "c",
+            Language::Cpp => "cpp",
+            Language::Java => "java",
+            Language::Python => "python",
+        }
+    }
+
+    #[must_use]
+    pub fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            Language::C => &["c"],
+            Language::Cpp => &["cpp", "cc", "cxx"],
+            Language::Java => &["java"],
+            Language::Python => &["py"],
+        }
+    }
+
+    #[must_use]
+    pub fn from_extension(extension: &str) -> Option<Language> {
+        let extension = extension.to_lowercase();
+
+        Language::ALL
+            .into_iter()
+            .find(|l| l.extensions().contains(&extension.as_str()))
+    }
+
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Language> {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .and_then(Language::from_extension)
+    }
+
+    pub(crate) fn tree_sitter(&self) -> tree_sitter::Language {
+        match self {
+            Language::C => tree_sitter_c::LANGUAGE.into(),
+            Language::Cpp => tree_sitter_cpp::LANGUAGE.into(),
+            Language::Java => tree_sitter_java::LANGUAGE.into(),
+            Language::Python => tree_sitter_python::LANGUAGE.into(),
+        }
+    }
+
+    pub(crate) fn grammar(&self) -> &'static Grammar {
+        match self {
+            Language::C => &C_GRAMMAR,
+            Language::Cpp => &CPP_GRAMMAR,
+            Language::Java => &JAVA_GRAMMAR,
+            Language::Python => &PYTHON_GRAMMAR,
+        }
+    }
+}
+
+impl FromStr for Language {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Language::ALL
+            .into_iter()
+            .find(|l| l.name() == s)
+            .ok_or_else(|| format!("unknown language `{s}` (expected c, cpp, java or python)"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyze_code_as;
+
+    fn constants(code: &str, language: Language) -> Vec<Token> {
+        analyze_code_as(code, language)
+            .unwrap()
+            .file_tokens
+            .into_iter()
+            .filter(|t| matches!(t, Token::ConstInt | Token::ConstFloat))
+            .collect()
+    }
+
+    #[test]
+    fn cpp_numbers_keep_their_kind() {
+        let code =
+            "int main() { int a = 1, b = 0x1F, c = 10ul; double d = 2.5, e = 1e3, f = .5f; }";
+
+        assert_eq!(
+            constants(code, Language::Cpp),
+            [
+                Token::ConstInt,
+                Token::ConstInt,
+                Token::ConstInt,
+                Token::ConstFloat,
+                Token::ConstFloat,
+                Token::ConstFloat,
+            ]
+        );
+    }
+
+    #[test]
+    fn c_numbers_and_characters_are_constants() {
+        let code = "int main() { int a = 1, b = 0x1E; char c = 'x'; double d = 2.5, e = 1e3; }";
+
+        assert_eq!(
+            constants(code, Language::C),
+            [
+                Token::ConstInt,
+                Token::ConstInt,
+                Token::ConstInt,
+                Token::ConstFloat,
+                Token::ConstFloat,
+            ]
+        );
+    }
+
+    #[test]
+    fn java_literals_keep_their_kind() {
+        let code = "class A { void f() { int a = 017, b = 0b101, c = 0xFF; char d = 'x'; \
+                    double e = 0x1p3, g = 2.5; } }";
+
+        assert_eq!(
+            constants(code, Language::Java),
+            [
+                Token::ConstInt,
+                Token::ConstInt,
+                Token::ConstInt,
+                Token::ConstInt,
+                Token::ConstFloat,
+                Token::ConstFloat,
+            ]
+        );
+    }
+
+    #[test]
+    fn java_overloads_are_kept_apart() {
+        let code = "class A {\n\
+                    int sum(int a, int b) { return a + b; }\n\
+                    double sum(double a, double b) { return a + b; }\n\
+                    int sum(int[] v) { int s = 0; for (int x : v) s += x; return s; }\n\
+                    }";
+
+        let functions = analyze_code_as(code, Language::Java).unwrap().functions;
+        let mut names: Vec<&String> = functions.keys().collect();
+        names.sort();
+
+        assert_eq!(names, ["sum", "sum#2", "sum#3"]);
+        assert!(functions["sum#3"].contains(&Token::Loop));
+    }
+}
@@ -1,14 +1,48 @@
-use regex::Regex;
-use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use tree_sitter::{Node, Parser};
 
-#[derive(Clone)]
+pub mod assignment;
+pub mod baseline;
+pub mod cluster;
+pub mod config;
+pub mod language;
+pub mod matching;
+pub mod metrics;
+mod preprocess;
+pub mod report;
+#[cfg(test)]
+mod test_support;
+pub mod winnowing;
+
+use assignment::max_weight_assignment;
+pub use baseline::subtract_baseline;
+pub use config::{SimilarityConfig, WinnowingConfig};
+use language::Grammar;
+pub use language::Language;
+pub use matching::{Fragment, matching_fragments};
+use metrics::{jaccard_similarity, lcs_similarity, levenshtein_similarity, weighted_cosine};
+use preprocess::preprocess;
+pub use report::{FunctionMatch, FunctionReport, MetricBreakdown, SimilarityReport};
+use winnowing::{Fingerprint, fingerprint, fingerprint_similarity};
+
+pub const ANALYZER_VERSION: u32 = 6;
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct AnalyzedFile {
     pub file_tokens: Vec<Token>,
+    pub file_spans: Vec<Span>,
     pub functions: HashMap<String, Vec<Token>>,
+    pub fingerprints: Vec<Fingerprint>,
+    pub discounted: f32,
+    pub language: Language,
+    /// Positions in `file_tokens` where starter code was cut out. Matching
+    /// fragments never span them.
+    #[serde(default)]
+    pub removed_at: Vec<usize>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Token {
     Var,
     Func,
@@ -23,6 +57,35 @@ pub enum Token {
     Other(String),
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Span {
+    pub start_byte: usize,
+    pub end_byte: usize,
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+impl Span {
+    fn from_node(node: Node) -> Self {
+        Self {
+            start_byte: node.start_byte(),
+            end_byte: node.end_byte(),
+            start_line: node.start_position().row + 1,
+            end_line: node.end_position().row + 1,
+        }
+    }
+
+    #[must_use]
+    pub fn merge(&self, other: &Span) -> Span {
+        Span {
+            start_byte: self.start_byte.min(other.start_byte),
+            end_byte: self.end_byte.max(other.end_byte),
+            start_line: self.start_line.min(other.start_line),
+            end_line: self.end_line.max(other.end_line),
+        }
+    }
+}
+
 impl Token {
     fn as_str(&self) -> &str {
         match self {
@@ -41,216 +104,226 @@ impl Token {
     }
 }
 
-fn remove_comments(code: &str) -> String {
-    let block_re = Regex::new(r"/\*.*?\*/").unwrap();
-    let line_re = Regex::new(r"//.*").unwrap();
-    let code = block_re.replace_all(code, "");
-    let code = line_re.replace_all(&code, "");
-    code.to_string()
+fn token_span(node: Node, token: &Token, grammar: &Grammar) -> Span {
+    let anchor = match token {
+        Token::Func if grammar.functions.contains(&node.kind()) => node
+            .child_by_field_name("declarator")
+            .or_else(|| node.child_by_field_name("name")),
+        Token::Func => node
+            .child_by_field_name("function")
+            .or_else(|| node.child_by_field_name("name")),
+        Token::Loop | Token::If => node.child(0),
+        _ => node.child_by_field_name("operator"),
+    };
+
+    Span::from_node(anchor.unwrap_or(node))
 }
 
-fn remove_hashtags(code: &str) -> String {
-    code.lines()
-        .filter(|l| !l.trim_start().starts_with('#'))
-        .collect::<Vec<_>>()
-        .join("\n")
-}
+fn number_token(literal: &str) -> Token {
+    let literal = literal.to_ascii_lowercase();
+    let is_hex = literal.starts_with("0x");
+
+    let is_float = literal.contains('.')
+        || (is_hex && literal.contains('p'))
+        || (!is_hex && literal.contains('e'));
 
-fn preprocess_code(code: &str) -> String {
-    let code = remove_comments(code);
-    let code = remove_hashtags(&code);
-    let code = Regex::new(r"typedef .*?;").unwrap().replace_all(&code, "");
-    let code = Regex::new(r"#define .*").unwrap().replace_all(&code, "");
-    code.to_string()
+    if is_float {
+        Token::ConstFloat
+    } else {
+        Token::ConstInt
+    }
 }
 
-fn extract_tokens(node: Node, source: &str, tokens: &mut Vec<Token>) {
-    match node.kind() {
-        "identifier" => tokens.push(Token::Var),
-        "function_definition" | "call_expression" => tokens.push(Token::Func),
-        "integer_literal" => tokens.push(Token::ConstInt),
-        "float_literal" => tokens.push(Token::ConstFloat),
-        "string_literal" => tokens.push(Token::ConstStr),
-        "for_statement" | "while_statement" | "do_statement" => tokens.push(Token::Loop),
-        "if_statement" => tokens.push(Token::If),
-        "binary_expression" => {
-            if let Some(op_node) = node.child_by_field_name("operator") {
-                let op = op_node.utf8_text(source.as_bytes()).unwrap();
-                match op {
-                    "+" | "-" | "*" | "/" | "%" => tokens.push(Token::ArithOp),
-                    "&&" | "||" | "!" => tokens.push(Token::LogicOp),
-                    "<" | ">" | "<=" | ">=" | "==" | "!=" => tokens.push(Token::RelOp),
-                    _ => tokens.push(Token::Other(op.to_string())),
-                }
-            }
-        }
-        _ => {}
+fn node_token(node: Node, source: &str, grammar: &Grammar) -> Option<Token> {
+    if let Some((_, token)) = grammar.tokens.iter().find(|(k, _)| *k == node.kind()) {
+        return Some(token.clone());
     }
 
-    for i in 0..node.child_count() {
-        if let Some(child) = node.child(i) {
-            extract_tokens(child, source, tokens);
-        }
+    if grammar.numbers.contains(&node.kind()) {
+        return Some(number_token(node.utf8_text(source.as_bytes()).unwrap()));
     }
-}
 
-fn extract_functions(node: Node, source: &str) -> HashMap<String, Vec<Token>> {
-    let mut functions = HashMap::new();
+    if !grammar.operators.contains(&node.kind()) {
+        return None;
+    }
 
-    if node.kind() == "function_definition" {
-        if let Some(declarator) = node.child_by_field_name("declarator") {
-            if let Some(name_node) = declarator
-                .child_by_field_name("identifier")
-                .or_else(|| declarator.named_child(0))
-            {
-                let func_name = name_node
-                    .utf8_text(source.as_bytes())
-                    .unwrap_or("<unnamed>");
-
-                let mut tokens = Vec::new();
-                extract_tokens(node, source, &mut tokens);
-                functions.insert(func_name.to_string(), tokens);
-            }
+    node.child_by_field_name("operator").map(|op_node| {
+        let op = op_node.utf8_text(source.as_bytes()).unwrap();
+        match op {
+            "+" | "-" | "*" | "/" | "%" | "//" | "**" => Token::ArithOp,
+            "&&" | "||" | "!" | "and" | "or" => Token::LogicOp,
+            "<" | ">" | "<=" | ">=" | "==" | "!=" => Token::RelOp,
+            _ => Token::Other(op.to_string()),
         }
+    })
+}
+
+fn extract_tokens(
+    node: Node,
+    source: &str,
+    grammar: &Grammar,
+    tokens: &mut Vec<Token>,
+    spans: &mut Vec<Span>,
+) {
+    if let Some(token) = node_token(node, source, grammar) {
+        spans.push(token_span(node, &token, grammar));
+        tokens.push(token);
     }
 
     for i in 0..node.child_count() {
         if let Some(child) = node.child(i) {
-            let nested = extract_functions(child, source);
-            functions.extend(nested);
+            extract_tokens(child, source, grammar, tokens, spans);
         }
     }
-
-    functions
 }
 
-fn jaccard_similarity(seq1: &[Token], seq2: &[Token]) -> f32 {
-    let set1: HashSet<_> = seq1.iter().collect();
-    let set2: HashSet<_> = seq2.iter().collect();
+fn extract_functions(
+    node: Node,
+    source: &str,
+    grammar: &Grammar,
+    functions: &mut HashMap<String, Vec<Token>>,
+) {
+    if grammar.functions.contains(&node.kind())
+        && let Some(name_node) = node.child_by_field_name("name").or_else(|| {
+            let declarator = node.child_by_field_name("declarator")?;
+            declarator
+                .child_by_field_name("identifier")
+                .or_else(|| declarator.named_child(0))
+        })
+    {
+        let func_name = name_node
+            .utf8_text(source.as_bytes())
+            .unwrap_or("<unnamed>");
+
+        let mut key = func_name.to_string();
+        let mut occurrence = 1;
+
+        while functions.contains_key(&key) {
+            occurrence += 1;
+            key = format!("{func_name}#{occurrence}");
+        }
 
-    if set1.is_empty() && set2.is_empty() {
-        return 1.0;
+        let mut tokens = Vec::new();
+        extract_tokens(node, source, grammar, &mut tokens, &mut Vec::new());
+        functions.insert(key, tokens);
     }
 
-    let intersection: HashSet<_> = set1.intersection(&set2).collect();
-    let union: HashSet<_> = set1.union(&set2).collect();
-
-    intersection.len() as f32 / union.len() as f32
-}
-
-fn lcs_similarity<T: PartialEq>(a: &[T], b: &[T]) -> f32 {
-    let m = a.len();
-    let n = b.len();
-    let mut dp = vec![vec![0; n + 1]; m + 1];
-
-    for i in 1..=m {
-        for j in 1..=n {
-            dp[i][j] = if a[i - 1] == b[j - 1] {
-                dp[i - 1][j - 1] + 1
-            } else {
-                dp[i - 1][j].max(dp[i][j - 1])
-            };
+    for i in 0..node.child_count() {
+        if let Some(child) = node.child(i) {
+            extract_functions(child, source, grammar, functions);
         }
     }
-
-    let lcs_len = dp[m][n];
-    lcs_len as f32 / ((m + n) as f32 / 2.0)
 }
 
-fn token_weight(token: &Token) -> f32 {
-    match token {
-        Token::If => 2.0,
-        Token::Loop => 2.0,
-        Token::Func => 1.5,
-        Token::Var => 0.5,
-        Token::ConstInt => 0.5,
-        Token::ConstFloat => 0.5,
-        Token::ConstStr => 0.5,
-        _ => 1.0,
-    }
+fn combined_similarity(
+    tokens1: &[Token],
+    tokens2: &[Token],
+    config: &SimilarityConfig,
+) -> MetricBreakdown {
+    let weights = &config.metrics;
+    let enabled = |weight: f32, metric: &dyn Fn() -> f32| (weight > 0.0).then(metric);
+
+    let mut breakdown = MetricBreakdown {
+        levenshtein: enabled(weights.levenshtein, &|| {
+            levenshtein_similarity(tokens1, tokens2)
+        }),
+        jaccard: enabled(weights.jaccard, &|| jaccard_similarity(tokens1, tokens2)),
+        lcs: enabled(weights.lcs, &|| lcs_similarity(tokens1, tokens2)),
+        cosine: enabled(weights.cosine, &|| {
+            weighted_cosine(tokens1, tokens2, &config.tokens)
+        }),
+        combined: 0.0,
+    };
+
+    let score = weights.levenshtein * breakdown.levenshtein.unwrap_or_default()
+        + weights.jaccard * breakdown.jaccard.unwrap_or_default()
+        + weights.lcs * breakdown.lcs.unwrap_or_default()
+        + weights.cosine * breakdown.cosine.unwrap_or_default();
+
+    breakdown.combined = score / weights.total();
+    breakdown
 }
 
-fn weighted_cosine(tokens1: &[Token], tokens2: &[Token]) -> f32 {
-    let all_tokens: HashSet<_> = tokens1.iter().chain(tokens2.iter()).collect();
-
-    let mut w1 = HashMap::new();
-    let mut w2 = HashMap::new();
-
-    for token in all_tokens.iter() {
-        let count1 = tokens1.iter().filter(|t| *t == *token).count() as f32;
-        let count2 = tokens2.iter().filter(|t| *t == *token).count() as f32;
-        w1.insert(*token, count1 * token_weight(token));
-        w2.insert(*token, count2 * token_weight(token));
-    }
-
-    let mut dot = 0.0;
-    let mut norm1 = 0.0;
-    let mut norm2 = 0.0;
-
-    for token in all_tokens {
-        let v1 = *w1.get(token).unwrap_or(&0.0);
-        let v2 = *w2.get(token).unwrap_or(&0.0);
-        dot += v1 * v2;
-        norm1 += v1 * v1;
-        norm2 += v2 * v2;
-    }
-
-    if norm1 == 0.0 || norm2 == 0.0 {
-        0.0
-    } else {
-        dot / (norm1.sqrt() * norm2.sqrt())
-    }
-}
-
-fn combined_similarity(tokens1: &[Token], tokens2: &[Token]) -> f32 {
-    let seq1_string: String = tokens1
-        .iter()
-        .map(|t| t.as_str())
-        .collect::<Vec<_>>()
-        .join(" ");
-    let seq2_string: String = tokens2
+fn funcoes_relevantes(
+    funcoes: &HashMap<String, Vec<Token>>,
+    min_tokens: usize,
+) -> Vec<(&String, &Vec<Token>)> {
+    let mut lista: Vec<_> = funcoes
         .iter()
-        .map(|t| t.as_str())
-        .collect::<Vec<_>>()
-        .join(" ");
-
-    let sm = strsim::normalized_levenshtein(&seq1_string, &seq2_string) as f32;
+        .filter(|(_, tokens)| tokens.len() >= min_tokens)
+        .collect();
 
-    let jacc = jaccard_similarity(tokens1, tokens2);
-    let lcs = lcs_similarity(tokens1, tokens2);
-    let cos = weighted_cosine(tokens1, tokens2);
-
-    0.4 * sm + 0.2 * jacc + 0.2 * lcs + 0.2 * cos
+    lista.sort_by(|a, b| a.0.cmp(b.0));
+    lista
 }
 
 fn similaridade_funcoes(
     funcoes1: &HashMap<String, Vec<Token>>,
     funcoes2: &HashMap<String, Vec<Token>>,
-) -> f32 {
-    if funcoes1.is_empty() || funcoes2.is_empty() {
-        return 0.0;
+    config: &SimilarityConfig,
+) -> Option<FunctionReport> {
+    let (lista1, lista2) = (
+        funcoes_relevantes(funcoes1, config.min_function_tokens),
+        funcoes_relevantes(funcoes2, config.min_function_tokens),
+    );
+
+    if lista1.is_empty() || lista2.is_empty() {
+        return None;
     }
 
-    let mut resultados = Vec::new();
+    let metricas: Vec<Vec<MetricBreakdown>> = lista1
+        .iter()
+        .map(|(_, t1)| {
+            lista2
+                .iter()
+                .map(|(_, t2)| combined_similarity(t1, t2, config))
+                .collect()
+        })
+        .collect();
+
+    let pesos: Vec<Vec<f32>> = lista1
+        .iter()
+        .zip(&metricas)
+        .map(|((_, t1), linha)| {
+            lista2
+                .iter()
+                .zip(linha)
+                .map(|((_, t2), m)| {
+                    let sim = if m.combined.is_nan() { 0.0 } else { m.combined };
+                    sim * (t1.len() + t2.len()) as f32
+                })
+                .collect()
+        })
+        .collect();
+
+    let tamanho_total: usize = lista1
+        .iter()
+        .chain(&lista2)
+        .map(|(_, tokens)| tokens.len())
+        .sum();
+
+    let pares = max_weight_assignment(&pesos);
+    let score = pares.iter().map(|&(i, j)| pesos[i][j]).sum::<f32>() / tamanho_total as f32;
 
-    for f1_tokens in funcoes1.values() {
-        for f2_tokens in funcoes2.values() {
-            let sim = combined_similarity(f1_tokens, f2_tokens);
-            resultados.push(sim);
-        }
-    }
+    let mut matches: Vec<FunctionMatch> = pares
+        .into_iter()
+        .map(|(i, j)| FunctionMatch {
+            left: lista1[i].0.clone(),
+            right: lista2[j].0.clone(),
+            metrics: metricas[i][j].clone(),
+        })
+        .collect();
 
-    *resultados
-        .iter()
-        .max_by(|a, b| a.partial_cmp(b).unwrap())
-        .unwrap_or(&0.0)
+    matches.sort_by(|a, b| b.metrics.combined.total_cmp(&a.metrics.combined));
+
+    Some(FunctionReport { score, matches })
 }
 
-fn parse_code(source: &str) -> tree_sitter::Tree {
+fn parse_code(source: &str, language: Language) -> tree_sitter::Tree {
     let mut parser = Parser::new();
     parser
-        .set_language(&tree_sitter_c::LANGUAGE.into())
-        .expect("Error loading C grammar");
+        .set_language(&language.tree_sitter())
+        .expect("Error loading grammar");
     parser.parse(source, None).unwrap()
 }
 
@@ -259,49 +332,195 @@ pub fn compare_two_codes(code1: &str, code2: &str) -> f32 {
         return -1.0;
     }
 
-    let mut questoes: Vec<(Vec<Token>, HashMap<String, Vec<Token>>)> = Vec::new();
-
-    for code in &[code1, code2] {
-        let code_clean = preprocess_code(code);
-
-        let tree = parse_code(&code_clean);
-
-        let mut file_tokens = Vec::new();
-        extract_tokens(tree.root_node(), &code_clean, &mut file_tokens);
-
-        let functions = extract_functions(tree.root_node(), &code_clean);
-
-        questoes.push((file_tokens, functions));
+    match (analyze_code(code1), analyze_code(code2)) {
+        (Some(a1), Some(a2)) => compare_two_codes_cached(&a1, &a2).score,
+        _ => -1.0,
     }
+}
 
-    let sim_arquivo = combined_similarity(&questoes[0].0, &questoes[1].0);
-    let sim_funcoes = similaridade_funcoes(&questoes[0].1, &questoes[1].1);
-
-    (sim_arquivo + sim_funcoes) / 2.0
+pub fn compare_two_codes_cached(
+    analyzed1: &AnalyzedFile,
+    analyzed2: &AnalyzedFile,
+) -> SimilarityReport {
+    compare_with_config(analyzed1, analyzed2, &SimilarityConfig::default())
 }
 
-pub fn compare_two_codes_cached(analyzed1: &AnalyzedFile, analyzed2: &AnalyzedFile) -> f32 {
-    let sim_arquivo = combined_similarity(&analyzed1.file_tokens, &analyzed2.file_tokens);
-    let sim_funcoes = similaridade_funcoes(&analyzed1.functions, &analyzed2.functions);
+pub fn compare_with_config(
+    analyzed1: &AnalyzedFile,
+    analyzed2: &AnalyzedFile,
+    config: &SimilarityConfig,
+) -> SimilarityReport {
+    let file = (config.file_weight > 0.0)
+        .then(|| combined_similarity(&analyzed1.file_tokens, &analyzed2.file_tokens, config));
+
+    let functions = (config.function_weight > 0.0)
+        .then(|| similaridade_funcoes(&analyzed1.functions, &analyzed2.functions, config))
+        .flatten();
+
+    let sim_arquivo = file.as_ref().map_or(0.0, |f| f.combined);
+    let sim_funcoes = functions.as_ref().map_or(0.0, |f| f.score);
+
+    SimilarityReport {
+        score: (config.file_weight * sim_arquivo + config.function_weight * sim_funcoes)
+            / (config.file_weight + config.function_weight),
+        file,
+        functions,
+    }
+}
 
-    (sim_arquivo + sim_funcoes) / 2.0
+pub fn compare_fingerprints(analyzed1: &AnalyzedFile, analyzed2: &AnalyzedFile) -> f32 {
+    fingerprint_similarity(&analyzed1.fingerprints, &analyzed2.fingerprints)
 }
 
 pub fn analyze_code(code: &str) -> Option<AnalyzedFile> {
+    analyze_code_as(code, Language::C)
+}
+
+pub fn analyze_code_as(code: &str, language: Language) -> Option<AnalyzedFile> {
     if code.trim().is_empty() {
         return None;
     }
 
-    let code_clean = preprocess_code(code);
-    let tree = parse_code(&code_clean);
+    let grammar = language.grammar();
+    let preprocessed = preprocess(code, language);
+    let tree = parse_code(&preprocessed.code, language);
 
     let mut file_tokens = Vec::new();
-    extract_tokens(tree.root_node(), &code_clean, &mut file_tokens);
+    let mut file_spans = Vec::new();
+    extract_tokens(
+        tree.root_node(),
+        &preprocessed.code,
+        grammar,
+        &mut file_tokens,
+        &mut file_spans,
+    );
+
+    let file_spans = file_spans
+        .iter()
+        .map(|s| preprocessed.original_span(s))
+        .collect();
 
-    let functions = extract_functions(tree.root_node(), &code_clean);
+    let mut functions = HashMap::new();
+    extract_functions(
+        tree.root_node(),
+        &preprocessed.code,
+        grammar,
+        &mut functions,
+    );
+    let fingerprints = fingerprint(&file_tokens, &WinnowingConfig::default());
 
     Some(AnalyzedFile {
         file_tokens,
+        file_spans,
         functions,
+        fingerprints,
+        discounted: 0.0,
+        language,
+        removed_at: vec![],
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MetricWeights;
+
+    const LEFT: &str = r#"
+int soma(int *v, int n) {
+    int total = 0;
+    for (int i = 0; i < n; i++) {
+        total += v[i];
+    }
+    return total;
+}
+
+int main() {
+    int v[3] = {1, 2, 3};
+    printf("%d\n", soma(v, 3));
+    return 0;
+}
+"#;
+
+    const RIGHT: &str = r#"
+int soma(int *v, int n) {
+    int total = 0;
+    for (int i = 0; i < n; i++) {
+        total += v[i];
+    }
+    return total;
+}
+
+int main() {
+    char nome[20];
+    scanf("%s", nome);
+    if (nome[0] == 'a') {
+        puts("ok");
+    }
+    return 0;
+}
+"#;
+
+    fn pair() -> (AnalyzedFile, AnalyzedFile) {
+        (analyze_code(LEFT).unwrap(), analyze_code(RIGHT).unwrap())
+    }
+
+    #[test]
+    fn report_breaks_the_score_down_by_metric_and_function() {
+        let (left, right) = pair();
+        let report = compare_with_config(&left, &right, &SimilarityConfig::default());
+
+        let file = report.file.as_ref().unwrap();
+        assert_eq!(file.values().len(), 4);
+        assert!(file.values().iter().all(|(_, v)| (0.0..=1.0).contains(v)));
+        assert!(file.combined < 1.0);
+
+        let functions = report.functions.as_ref().unwrap();
+        let names: Vec<(&str, &str)> = functions
+            .matches
+            .iter()
+            .map(|m| (m.left.as_str(), m.right.as_str()))
+            .collect();
+        assert_eq!(names, [("soma", "soma"), ("main", "main")]);
+
+        let closest = functions.closest().unwrap();
+        assert_eq!(closest.left, "soma");
+        assert!((closest.metrics.combined - 1.0).abs() < 1e-6);
+        assert!(functions.matches[1].metrics.combined < 1.0);
+
+        let expected = (file.combined + functions.score) / 2.0;
+        assert!((report.score - expected).abs() < 1e-6);
+
+        let mirrored = report.mirrored();
+        assert_eq!(mirrored.score, report.score);
+        assert_eq!(mirrored.file, report.file);
+    }
+
+    #[test]
+    fn disabled_metrics_and_parts_are_left_out() {
+        let (left, right) = pair();
+        let config = SimilarityConfig {
+            function_weight: 0.0,
+            metrics: MetricWeights {
+                lcs: 0.0,
+                cosine: 0.0,
+                ..MetricWeights::default()
+            },
+            ..SimilarityConfig::default()
+        };
+
+        let report = compare_with_config(&left, &right, &config);
+        let file = report.file.as_ref().unwrap();
+
+        assert!(report.functions.is_none());
+        assert_eq!(file.lcs, None);
+        assert_eq!(file.cosine, None);
+        assert_eq!(
+            file.values()
+                .iter()
+                .map(|(name, _)| *name)
+                .collect::<Vec<_>>(),
+            ["Levenshtein", "Jaccard"]
+        );
+        assert!((report.score - file.combined).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+
+use crate::{AnalyzedFile, Span, Token};
+
+pub const MIN_FRAGMENT_TOKENS: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fragment {
+    pub left: Span,
+    pub right: Span,
+    pub tokens: usize,
+}
+
+impl Fragment {
+    #[must_use]
+    pub fn mirrored(&self) -> Fragment {
+        Fragment {
+            left: self.right,
+            right: self.left,
+            tokens: self.tokens,
+        }
+    }
+}
+
+fn span_of(spans: &[Span], start: usize, len: usize) -> Span {
+    spans[start + 1..start + len]
+        .iter()
+        .fold(spans[start], |acc, s| acc.merge(s))
+}
+
+pub(crate) fn greedy_string_tiling(
+    a: &[Token],
+    b: &[Token],
+    min_len: usize,
+) -> Vec<(usize, usize, usize)> {
+    tiling_within(a, &[], b, &[], min_len)
+}
+
+fn segments(len: usize, breaks: &[usize]) -> Vec<usize> {
+    let mut segments = vec![0; len];
+
+    for &b in breaks.iter().filter(|b| **b < len) {
+        segments[b] += 1;
+    }
+
+    for i in 1..len {
+        segments[i] += segments[i - 1];
+    }
+
+    segments
+}
+
+/// Greedy string tiling where no tile crosses a break, a position at which
+/// tokens were removed from the stream.
+pub(crate) fn tiling_within(
+    a: &[Token],
+    breaks_a: &[usize],
+    b: &[Token],
+    breaks_b: &[usize],
+    min_len: usize,
+) -> Vec<(usize, usize, usize)> {
+    let segment_a = segments(a.len(), breaks_a);
+    let segment_b = segments(b.len(), breaks_b);
+    let mut marked_a = vec![false; a.len()];
+    let mut marked_b = vec![false; b.len()];
+    let mut tiles = vec![];
+
+    if min_len == 0 || a.len() < min_len || b.len() < min_len {
+        return tiles;
+    }
+
+    let mut seeds: HashMap<&[Token], Vec<usize>> = HashMap::new();
+    for j in 0..=(b.len() - min_len) {
+        seeds.entry(&b[j..j + min_len]).or_default().push(j);
+    }
+
+    loop {
+        let mut max_len = min_len;
+        let mut matches: Vec<(usize, usize, usize)> = vec![];
+
+        for i in 0..=(a.len() - min_len) {
+            let Some(candidates) = seeds.get(&a[i..i + min_len]) else {
+                continue;
+            };
+
+            for &j in candidates {
+                let mut len = 0;
+                while i + len < a.len()
+                    && j + len < b.len()
+                    && !marked_a[i + len]
+                    && !marked_b[j + len]
+                    && segment_a[i + len] == segment_a[i]
+                    && segment_b[j + len] == segment_b[j]
+                    && a[i + len] == b[j + len]
+                {
+                    len += 1;
+                }
+
+                if len > max_len {
+                    max_len = len;
+                    matches.clear();
+                }
+
+                if len == max_len {
+                    matches.push((i, j, len));
+                }
+            }
+        }
+
+        if matches.is_empty() {
+            break;
+        }
+
+        for (i, j, len) in matches {
+            if marked_a[i..i + len].iter().any(|m| *m) || marked_b[j..j + len].iter().any(|m| *m) {
+                continue;
+            }
+
+            marked_a[i..i + len].fill(true);
+            marked_b[j..j + len].fill(true);
+            tiles.push((i, j, len));
+        }
+    }
+
+    tiles.sort_unstable();
+    tiles
+}
+
+pub fn matching_fragments(
+    analyzed1: &AnalyzedFile,
+    analyzed2: &AnalyzedFile,
+    min_tokens: usize,
+) -> Vec<Fragment> {
+    tiling_within(
+        &analyzed1.file_tokens,
+        &analyzed1.removed_at,
+        &analyzed2.file_tokens,
+        &analyzed2.removed_at,
+        min_tokens,
+    )
+    .into_iter()
+    .map(|(i, j, len)| Fragment {
+        left: span_of(&analyzed1.file_spans, i, len),
+        right: span_of(&analyzed2.file_spans, j, len),
+        tokens: len,
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Lcg;
+
+    fn naive_tiling(a: &[Token], b: &[Token], min_len: usize) -> Vec<(usize, usize, usize)> {
+        let mut marked_a = vec![false; a.len()];
+        let mut marked_b = vec![false; b.len()];
+        let mut tiles = vec![];
+
+        loop {
+            let mut best = (0, 0, 0);
+
+            for i in 0..a.len() {
+                for j in 0..b.len() {
+                    let mut len = 0;
+                    while i + len < a.len()
+                        && j + len < b.len()
+                        && !marked_a[i + len]
+                        && !marked_b[j + len]
+                        && a[i + len] == b[j + len]
+                    {
+                        len += 1;
+                    }
+
+                    if len > best.2 {
+                        best = (i, j, len);
+                    }
+                }
+            }
+
+            let (i, j, len) = best;
+
+            if len < min_len.max(1) {
+                break;
+            }
+
+            marked_a[i..i + len].fill(true);
+            marked_b[j..j + len].fill(true);
+            tiles.push(best);
+        }
+
+        tiles.sort_unstable();
+        tiles
+    }
+
+    #[test]
+    fn tiling_matches_exhaustive_search() {
+        let mut rng = Lcg::new(3);
+
+        for _ in 0..200 {
+            let (m, n) = (rng.below(80), rng.below(80));
+            let min_len = 1 + rng.below(6);
+            let alphabet = 2 + rng.below(4);
+            let a = rng.tokens(m, alphabet);
+            let b = rng.tokens(n, alphabet);
+
+            assert_eq!(
+                greedy_string_tiling(&a, &b, min_len),
+                naive_tiling(&a, &b, min_len),
+                "{a:?} {b:?} {min_len}"
+            );
+        }
+    }
+
+    #[test]
+    fn tiles_are_disjoint_and_equal() {
+        let mut rng = Lcg::new(4);
+        let shared = rng.tokens(30, 8);
+        let a = [rng.tokens(20, 8), shared.clone(), rng.tokens(5, 8)].concat();
+        let b = [shared.clone(), rng.tokens(40, 8), shared].concat();
+
+        let tiles = greedy_string_tiling(&a, &b, 5);
+        let mut covered_a = vec![false; a.len()];
+        let mut covered_b = vec![false; b.len()];
+
+        assert!(tiles.iter().any(|&(i, _, len)| i == 20 && len >= 30));
+
+        for (i, j, len) in tiles {
+            assert!(len >= 5);
+            assert_eq!(a[i..i + len], b[j..j + len]);
+            assert!(covered_a[i..i + len].iter().all(|c| !c));
+            assert!(covered_b[j..j + len].iter().all(|c| !c));
+            covered_a[i..i + len].fill(true);
+            covered_b[j..j + len].fill(true);
+        }
+    }
+
+    #[test]
+    fn short_inputs_produce_no_tiles() {
+        let a = vec![Token::Var; 4];
+
+        assert!(greedy_string_tiling(&a, &a, 5).is_empty());
+        assert!(greedy_string_tiling(&a, &a, 0).is_empty());
+        assert!(greedy_string_tiling(&[], &a, 1).is_empty());
+    }
+}
@@ -0,0 +1,272 @@
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use crate::{Token, config::TokenWeights};
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+pub fn jaccard_similarity(seq1: &[Token], seq2: &[Token]) -> f32 {
+    let set1: HashSet<_> = seq1.iter().collect();
+    let set2: HashSet<_> = seq2.iter().collect();
+
+    if set1.is_empty() && set2.is_empty() {
+        return 1.0;
+    }
+
+    let intersection = set1.intersection(&set2).count();
+    let union = set1.union(&set2).count();
+
+    intersection as f32 / union as f32
+}
+
+pub fn lcs_length<T: Eq + Hash>(a: &[T], b: &[T]) -> usize {
+    let (a, b) = if a.len() > b.len() { (b, a) } else { (a, b) };
+
+    if a.is_empty() {
+        return 0;
+    }
+
+    let words = a.len().div_ceil(WORD_BITS);
+    let mut peq: HashMap<&T, Vec<u64>> = HashMap::new();
+
+    for (i, item) in a.iter().enumerate() {
+        peq.entry(item).or_insert_with(|| vec![0; words])[i / WORD_BITS] |= 1 << (i % WORD_BITS);
+    }
+
+    let mut v = vec![u64::MAX; words];
+
+    for item in b {
+        let Some(eqs) = peq.get(item) else {
+            continue;
+        };
+
+        let mut carry = false;
+
+        for w in 0..words {
+            let u = v[w] & eqs[w];
+            let (sum, c1) = v[w].overflowing_add(u);
+            let (sum, c2) = sum.overflowing_add(carry as u64);
+            carry = c1 || c2;
+            v[w] = sum | (v[w] & !eqs[w]);
+        }
+    }
+
+    v.iter().map(|w| w.count_zeros() as usize).sum()
+}
+
+pub fn lcs_similarity<T: Eq + Hash>(a: &[T], b: &[T]) -> f32 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+
+    lcs_length(a, b) as f32 / ((a.len() + b.len()) as f32 / 2.0)
+}
+
+fn token_bytes(tokens: &[Token]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(tokens.len() * 8);
+
+    for (i, token) in tokens.iter().enumerate() {
+        if i > 0 {
+            bytes.push(b' ');
+        }
+
+        bytes.extend_from_slice(token.as_str().as_bytes());
+    }
+
+    bytes
+}
+
+pub fn levenshtein(a: &[u8], b: &[u8]) -> usize {
+    let (a, b) = if a.len() > b.len() { (b, a) } else { (a, b) };
+
+    if a.is_empty() {
+        return b.len();
+    }
+
+    let words = a.len().div_ceil(WORD_BITS);
+    let last = 1u64 << ((a.len() - 1) % WORD_BITS);
+
+    let mut peq = vec![0u64; 256 * words];
+
+    for (i, c) in a.iter().enumerate() {
+        peq[*c as usize * words + i / WORD_BITS] |= 1 << (i % WORD_BITS);
+    }
+
+    let mut vp = vec![u64::MAX; words];
+    let mut vn = vec![0u64; words];
+    let mut score = a.len();
+
+    for c in b {
+        let eqs = &peq[*c as usize * words..(*c as usize + 1) * words];
+
+        let mut h_in: i8 = 1;
+
+        for w in 0..words {
+            let high = if w == words - 1 { last } else { 1 << 63 };
+
+            let mut eq = eqs[w];
+            let xv = eq | vn[w];
+
+            if h_in < 0 {
+                eq |= 1;
+            }
+
+            let xh = (((eq & vp[w]).wrapping_add(vp[w])) ^ vp[w]) | eq;
+            let mut hp = vn[w] | !(xh | vp[w]);
+            let mut hn = vp[w] & xh;
+
+            let h_out = if hp & high != 0 {
+                1
+            } else if hn & high != 0 {
+                -1
+            } else {
+                0
+            };
+
+            hp <<= 1;
+            hn <<= 1;
+
+            if h_in < 0 {
+                hn |= 1;
+            } else if h_in > 0 {
+                hp |= 1;
+            }
+
+            vp[w] = hn | !(xv | hp);
+            vn[w] = hp & xv;
+
+            h_in = h_out;
+        }
+
+        score = score.saturating_add_signed(h_in as isize);
+    }
+
+    score
+}
+
+pub fn levenshtein_similarity(tokens1: &[Token], tokens2: &[Token]) -> f32 {
+    let (a, b) = (token_bytes(tokens1), token_bytes(tokens2));
+
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+
+    (1.0 - levenshtein(&a, &b) as f64 / a.len().max(b.len()) as f64) as f32
+}
+
+pub fn weighted_cosine(tokens1: &[Token], tokens2: &[Token], weights: &TokenWeights) -> f32 {
+    let mut counts: HashMap<&Token, (f32, f32)> = HashMap::new();
+
+    for token in tokens1 {
+        counts.entry(token).or_default().0 += 1.0;
+    }
+
+    for token in tokens2 {
+        counts.entry(token).or_default().1 += 1.0;
+    }
+
+    let mut dot = 0.0;
+    let mut norm1 = 0.0;
+    let mut norm2 = 0.0;
+
+    for (token, (count1, count2)) in counts {
+        let v1 = count1 * weights.weight(token);
+        let v2 = count2 * weights.weight(token);
+        dot += v1 * v2;
+        norm1 += v1 * v1;
+        norm2 += v2 * v2;
+    }
+
+    if norm1 == 0.0 || norm2 == 0.0 {
+        0.0
+    } else {
+        dot / (norm1.sqrt() * norm2.sqrt())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Lcg;
+
+    const LENGTHS: [usize; 9] = [0, 1, 7, 63, 64, 65, 127, 130, 200];
+
+    fn naive_lcs<T: Eq>(a: &[T], b: &[T]) -> usize {
+        let mut dp = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+        for i in 1..=a.len() {
+            for j in 1..=b.len() {
+                dp[i][j] = if a[i - 1] == b[j - 1] {
+                    dp[i - 1][j - 1] + 1
+                } else {
+                    dp[i - 1][j].max(dp[i][j - 1])
+                };
+            }
+        }
+
+        dp[a.len()][b.len()]
+    }
+
+    #[test]
+    fn lcs_length_matches_dynamic_programming() {
+        let mut rng = Lcg::new(16);
+
+        for &m in &LENGTHS {
+            for &n in &LENGTHS {
+                for alphabet in [2, 5, 40] {
+                    let a = rng.tokens(m, alphabet);
+                    let b = rng.tokens(n, alphabet);
+
+                    assert_eq!(lcs_length(&a, &b), naive_lcs(&a, &b), "{m}x{n}/{alphabet}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn levenshtein_matches_dynamic_programming() {
+        let mut rng = Lcg::new(17);
+
+        for &m in &LENGTHS {
+            for &n in &LENGTHS {
+                for alphabet in [2, 4, 26] {
+                    let a = rng.bytes(m, alphabet);
+                    let b = rng.bytes(n, alphabet);
+
+                    assert_eq!(
+                        levenshtein(&a, &b),
+                        strsim::generic_levenshtein(&a, &b),
+                        "{m}x{n}/{alphabet}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn levenshtein_handles_edits_across_word_boundaries() {
+        let a = vec![b'x'; 200];
+        let mut b = a.clone();
+        b[63] = b'y';
+        b[64] = b'y';
+        b.insert(128, b'z');
+        b.remove(10);
+
+        assert_eq!(levenshtein(&a, &b), strsim::generic_levenshtein(&a, &b));
+    }
+
+    #[test]
+    fn empty_inputs_have_defined_scores() {
+        let empty: [Token; 0] = [];
+        let tokens = [Token::Var, Token::If];
+
+        assert_eq!(lcs_similarity(&empty, &empty), 1.0);
+        assert_eq!(lcs_similarity(&empty, &tokens), 0.0);
+        assert_eq!(levenshtein_similarity(&empty, &empty), 1.0);
+        assert_eq!(levenshtein_similarity(&empty, &tokens), 0.0);
+        assert_eq!(jaccard_similarity(&empty, &empty), 1.0);
+        assert_eq!(jaccard_similarity(&tokens, &tokens), 1.0);
+    }
+}
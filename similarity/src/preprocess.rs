@@ -0,0 +1,687 @@
+use std::collections::HashMap;
+use tree_sitter::Node;
+
+use crate::{Language, Span, parse_code};
+
+const MAX_EXPANSION_DEPTH: usize = 64;
+
+struct Macro {
+    defined_at: usize,
+    alias: bool,
+    params: Option<Vec<String>>,
+    body: String,
+}
+
+type Macros = HashMap<String, Macro>;
+
+pub(crate) struct Preprocessed {
+    pub code: String,
+    origins: Vec<(usize, usize)>,
+    line_starts: Vec<usize>,
+}
+
+impl Preprocessed {
+    pub fn original_span(&self, span: &Span) -> Span {
+        let origin = |i: usize| {
+            self.origins
+                .get(i)
+                .copied()
+                .unwrap_or_else(|| self.origins.last().copied().unwrap_or_default())
+        };
+
+        let start_byte = origin(span.start_byte).0;
+        let end_byte = if span.end_byte > span.start_byte {
+            origin(span.end_byte - 1).1
+        } else {
+            start_byte
+        };
+
+        let line = |byte: usize| self.line_starts.partition_point(|&s| s <= byte);
+
+        Span {
+            start_byte,
+            end_byte,
+            start_line: line(start_byte),
+            end_line: line(end_byte.saturating_sub(1).max(start_byte)),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Builder {
+    code: Vec<u8>,
+    origins: Vec<(usize, usize)>,
+}
+
+impl Builder {
+    fn copy(&mut self, text: &[u8], origins: &[(usize, usize)]) {
+        self.code.extend_from_slice(text);
+        self.origins.extend_from_slice(origins);
+    }
+
+    fn insert(&mut self, text: &str, origin: (usize, usize)) {
+        self.code.extend_from_slice(text.as_bytes());
+        self.origins.extend(std::iter::repeat_n(origin, text.len()));
+    }
+}
+
+fn blank(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            if c == '\n' {
+                "\n".to_string()
+            } else {
+                " ".repeat(c.len_utf8())
+            }
+        })
+        .collect()
+}
+
+fn follows_tag_keyword(text: &str) -> bool {
+    let word = text
+        .trim_end()
+        .rsplit(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+        .next();
+
+    matches!(word, Some("struct" | "union" | "enum"))
+}
+
+fn removed_kinds(language: Language) -> &'static [&'static str] {
+    match language {
+        Language::C | Language::Cpp => &["comment", "preproc_include", "preproc_call"],
+        Language::Java => &[
+            "line_comment",
+            "block_comment",
+            "import_declaration",
+            "package_declaration",
+        ],
+        Language::Python => &[
+            "comment",
+            "import_statement",
+            "import_from_statement",
+            "future_import_statement",
+        ],
+    }
+}
+
+struct Collector<'a> {
+    source: &'a str,
+    removed: &'static [&'static str],
+    edits: Vec<(usize, usize, String)>,
+    macros: Macros,
+}
+
+impl<'a> Collector<'a> {
+    fn text(&self, node: Node) -> &'a str {
+        &self.source[node.byte_range()]
+    }
+
+    fn remove(&mut self, node: Node) {
+        self.edits
+            .push((node.start_byte(), node.end_byte(), blank(self.text(node))));
+    }
+
+    fn define(
+        &mut self,
+        name: &str,
+        defined_at: usize,
+        alias: bool,
+        params: Option<Vec<String>>,
+        body: &str,
+    ) {
+        self.macros.insert(
+            name.to_string(),
+            Macro {
+                defined_at,
+                alias,
+                params,
+                body: body
+                    .replace("\\\r\n", " ")
+                    .replace("\\\n", " ")
+                    .trim()
+                    .to_string(),
+            },
+        );
+    }
+
+    fn visit(&mut self, node: Node) {
+        if self.removed.contains(&node.kind()) {
+            self.remove(node);
+            return;
+        }
+
+        match node.kind() {
+            "preproc_def" | "preproc_function_def" => {
+                if let Some(name) = node.child_by_field_name("name") {
+                    let params = node.child_by_field_name("parameters").map(|p| {
+                        let mut cursor = p.walk();
+                        p.children(&mut cursor)
+                            .filter_map(|c| match c.kind() {
+                                "identifier" => Some(self.text(c).to_string()),
+                                "..." => Some("__VA_ARGS__".to_string()),
+                                _ => None,
+                            })
+                            .collect()
+                    });
+                    let body = node
+                        .child_by_field_name("value")
+                        .map_or("", |v| self.text(v))
+                        .to_string();
+
+                    self.define(self.text(name), node.end_byte(), false, params, &body);
+                }
+
+                self.remove(node);
+            }
+            "preproc_if" | "preproc_ifdef" | "preproc_else" | "preproc_elif"
+            | "preproc_elifdef" => {
+                let mut cursor = node.walk();
+
+                for (i, child) in node.children(&mut cursor).enumerate() {
+                    let field = node.field_name_for_child(i as u32);
+
+                    if !child.is_named() || matches!(field, Some("name" | "condition")) {
+                        self.remove(child);
+                    } else {
+                        self.visit(child);
+                    }
+                }
+            }
+            "type_definition" => self.typedef(node),
+            _ => {
+                let mut cursor = node.walk();
+
+                for child in node.children(&mut cursor) {
+                    self.visit(child);
+                }
+            }
+        }
+    }
+
+    fn typedef(&mut self, node: Node) {
+        let mut cursor = node.walk();
+        let declarators: Vec<Node> = node
+            .children_by_field_name("declarator", &mut cursor)
+            .collect();
+
+        let (Some(ty), Some(first)) = (node.child_by_field_name("type"), declarators.first())
+        else {
+            return;
+        };
+
+        let mut aliases = Vec::new();
+
+        for declarator in &declarators {
+            let mut pointers = String::new();
+            let mut current = *declarator;
+
+            while current.kind() == "pointer_declarator" {
+                pointers.push('*');
+                match current.child_by_field_name("declarator") {
+                    Some(inner) => current = inner,
+                    None => return,
+                }
+            }
+
+            if current.kind() != "type_identifier" {
+                return;
+            }
+
+            aliases.push((self.text(current).to_string(), pointers));
+        }
+
+        let end = node.end_byte();
+        let keyword = ty.child(0).map_or("struct", |k| self.text(k)).to_string();
+
+        let (underlying, replacement) = match (
+            ty.child_by_field_name("body"),
+            ty.child_by_field_name("name"),
+        ) {
+            (Some(_), Some(tag)) => (
+                format!("{keyword} {}", self.text(tag)),
+                format!("{};", self.text(ty)),
+            ),
+            (Some(body), None) => {
+                let tag = &aliases[0].0;
+                (
+                    format!("{keyword} {tag}"),
+                    format!("{keyword} {tag} {};", self.text(body)),
+                )
+            }
+            (None, _) => {
+                let prefix = self.source[node.start_byte()..first.start_byte()]
+                    .trim_start()
+                    .trim_start_matches("typedef")
+                    .trim()
+                    .to_string();
+                (prefix, blank(self.text(node)))
+            }
+        };
+
+        for (alias, pointers) in aliases {
+            let body = if pointers.is_empty() {
+                underlying.clone()
+            } else {
+                format!("{underlying} {pointers}")
+            };
+            self.define(&alias, end, true, None, &body);
+        }
+
+        self.edits.push((node.start_byte(), end, replacement));
+    }
+}
+
+fn is_ident_start(c: u8) -> bool {
+    c.is_ascii_alphabetic() || c == b'_'
+}
+
+fn is_ident(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_'
+}
+
+fn literal_end(text: &[u8], start: usize) -> usize {
+    let quote = text[start];
+    let mut i = start + 1;
+
+    while i < text.len() && text[i] != quote && text[i] != b'\n' {
+        i += if text[i] == b'\\' { 2 } else { 1 };
+    }
+
+    (i + 1).min(text.len())
+}
+
+fn call_arguments(text: &[u8], open: usize) -> Option<(Vec<(usize, usize)>, usize)> {
+    let mut args = Vec::new();
+    let mut depth = 0;
+    let mut arg_start = open + 1;
+    let mut i = open;
+
+    while i < text.len() {
+        match text[i] {
+            b'"' | b'\'' => {
+                i = literal_end(text, i);
+                continue;
+            }
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => {
+                depth -= 1;
+
+                if depth == 0 {
+                    args.push((arg_start, i));
+                    return Some((args, i + 1));
+                }
+            }
+            b',' if depth == 1 => {
+                args.push((arg_start, i));
+                arg_start = i + 1;
+            }
+            _ => {}
+        }
+
+        i += 1;
+    }
+
+    None
+}
+
+fn stringify(arg: &str) -> String {
+    format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn expand_to_string(text: &str, macros: &Macros, active: &mut Vec<String>) -> String {
+    let mut out = String::new();
+    expand(text, macros, active, &|_, _| true, &mut |piece, _, _, _| {
+        out.push_str(piece)
+    });
+    out
+}
+
+fn substitute(
+    name: &str,
+    params: &[String],
+    body: &str,
+    args: &[&str],
+    macros: &Macros,
+    active: &mut Vec<String>,
+) -> String {
+    let argument = |param: &str| -> Option<String> {
+        if param == "__VA_ARGS__" {
+            let fixed = params.iter().filter(|p| *p != "__VA_ARGS__").count();
+            return Some(args.get(fixed..).unwrap_or_default().join(", "));
+        }
+
+        params
+            .iter()
+            .position(|p| p == param)
+            .map(|i| args.get(i).copied().unwrap_or_default().to_string())
+    };
+
+    let bytes = body.as_bytes();
+    let mut out = String::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let c = bytes[i];
+
+        if c == b'"' || c == b'\'' {
+            let end = literal_end(bytes, i);
+            out.push_str(&body[i..end]);
+            i = end;
+        } else if is_ident_start(c) {
+            let mut end = i;
+            while end < bytes.len() && is_ident(bytes[end]) {
+                end += 1;
+            }
+
+            match argument(&body[i..end]) {
+                Some(raw) => {
+                    let trimmed = out.trim_end();
+
+                    if trimmed.ends_with('#') && !trimmed.ends_with("##") {
+                        out.truncate(trimmed.len() - 1);
+                        out.push_str(&stringify(&raw));
+                    } else {
+                        out.push_str(&expand_to_string(&raw, macros, active));
+                    }
+                }
+                None => out.push_str(&body[i..end]),
+            }
+
+            i = end;
+        } else {
+            let ch = body[i..].chars().next().unwrap();
+            out.push(ch);
+            i += ch.len_utf8();
+        }
+    }
+
+    let pasted = out.split("##").map(str::trim).collect::<Vec<_>>().join("");
+
+    active.push(name.to_string());
+    let expanded = expand_to_string(&pasted, macros, active);
+    active.pop();
+
+    expanded
+}
+
+fn expand(
+    text: &str,
+    macros: &Macros,
+    active: &mut Vec<String>,
+    visible: &dyn Fn(&Macro, usize) -> bool,
+    emit: &mut dyn FnMut(&str, usize, usize, bool),
+) {
+    let bytes = text.as_bytes();
+    let mut copied_from = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let c = bytes[i];
+
+        if c == b'"' || c == b'\'' {
+            i = literal_end(bytes, i);
+            continue;
+        }
+
+        if c.is_ascii_digit() {
+            while i < bytes.len() && (is_ident(bytes[i]) || bytes[i] == b'.') {
+                i += 1;
+            }
+            continue;
+        }
+
+        if !is_ident_start(c) {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        while i < bytes.len() && is_ident(bytes[i]) {
+            i += 1;
+        }
+
+        let name = &text[start..i];
+
+        let Some(m) = macros.get(name) else {
+            continue;
+        };
+
+        if !visible(m, start)
+            || (m.alias && follows_tag_keyword(&text[..start]))
+            || active.iter().any(|a| a == name)
+            || active.len() > MAX_EXPANSION_DEPTH
+        {
+            continue;
+        }
+
+        let (expansion, end) = match &m.params {
+            None => {
+                active.push(name.to_string());
+                let expansion = expand_to_string(&m.body, macros, active);
+                active.pop();
+                (expansion, i)
+            }
+            Some(params) => {
+                let open = i + text[i..].len() - text[i..].trim_start().len();
+
+                if bytes.get(open) != Some(&b'(') {
+                    continue;
+                }
+
+                let Some((ranges, end)) = call_arguments(bytes, open) else {
+                    continue;
+                };
+
+                let mut args: Vec<&str> = ranges.iter().map(|&(s, e)| text[s..e].trim()).collect();
+
+                if params.is_empty() && args == [""] {
+                    args.clear();
+                }
+
+                (
+                    substitute(name, params, &m.body, &args, macros, active),
+                    end,
+                )
+            }
+        };
+
+        emit(&text[copied_from..start], copied_from, start, true);
+        emit(&expansion, start, end, false);
+        copied_from = end;
+        i = end;
+    }
+
+    emit(&text[copied_from..], copied_from, text.len(), true);
+}
+
+pub(crate) fn preprocess(code: &str, language: Language) -> Preprocessed {
+    let tree = parse_code(code, language);
+
+    let mut collector = Collector {
+        source: code,
+        removed: removed_kinds(language),
+        edits: Vec::new(),
+        macros: HashMap::new(),
+    };
+    collector.visit(tree.root_node());
+
+    let mut edits = collector.edits;
+    edits.sort_by_key(|e| e.0);
+
+    let mut cleaned = Builder::default();
+    let mut position = 0;
+
+    for (start, end, replacement) in edits {
+        if start < position {
+            continue;
+        }
+
+        let identity: Vec<_> = (position..start).map(|i| (i, i + 1)).collect();
+        cleaned.copy(&code.as_bytes()[position..start], &identity);
+        cleaned.insert(&replacement, (start, end));
+        position = end;
+    }
+
+    let identity: Vec<_> = (position..code.len()).map(|i| (i, i + 1)).collect();
+    cleaned.copy(&code.as_bytes()[position..], &identity);
+
+    let cleaned_code = String::from_utf8(cleaned.code).unwrap();
+    let macros = collector.macros;
+
+    let mut expanded = Builder::default();
+    let origins = &cleaned.origins;
+
+    expand(
+        &cleaned_code,
+        &macros,
+        &mut Vec::new(),
+        &|m, at| origins[at].0 >= m.defined_at,
+        &mut |piece, start, end, copied| {
+            if copied {
+                expanded.copy(piece.as_bytes(), &origins[start..end]);
+            } else {
+                expanded.insert(piece, (origins[start].0, origins[end - 1].1));
+            }
+        },
+    );
+
+    let mut line_starts = vec![0];
+    line_starts.extend(
+        code.bytes()
+            .enumerate()
+            .filter(|(_, b)| *b == b'\n')
+            .map(|(i, _)| i + 1),
+    );
+
+    Preprocessed {
+        code: String::from_utf8(expanded.code).unwrap(),
+        origins: expanded.origins,
+        line_starts,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expanded(code: &str) -> String {
+        preprocess(code, Language::C)
+            .code
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn origin_of<'a>(code: &'a str, needle: &str) -> (&'a str, usize) {
+        let preprocessed = preprocess(code, Language::C);
+        let start_byte = preprocessed.code.find(needle).unwrap();
+        let span = preprocessed.original_span(&Span {
+            start_byte,
+            end_byte: start_byte + needle.len(),
+            start_line: 0,
+            end_line: 0,
+        });
+
+        (&code[span.start_byte..span.end_byte], span.start_line)
+    }
+
+    #[test]
+    fn expands_object_and_function_macros() {
+        let code = "#define N 10\n\
+                    #define SQ(x) ((x) * (x))\n\
+                    #define STR(x) #x\n\
+                    #define CAT(a, b) a##b\n\
+                    #define LOG(fmt, ...) printf(fmt, __VA_ARGS__)\n\
+                    int main() { int CAT(fo, o) = SQ(N + 1); LOG(\"%d\", foo); puts(STR(hi)); }\n";
+
+        assert_eq!(
+            expanded(code),
+            "int main() { int foo = ((10 + 1) * (10 + 1)); \
+             printf(\"%d\", foo); puts(\"hi\"); }"
+        );
+    }
+
+    #[test]
+    fn macros_apply_only_after_their_definition() {
+        let code = "int before = N;\n#define N 3\nint after = N;\n";
+
+        assert_eq!(expanded(code), "int before = N; int after = 3;");
+    }
+
+    #[test]
+    fn replaces_typedef_names() {
+        let code = "typedef unsigned long ul;\n\
+                    typedef struct { int a; } P, *PP;\n\
+                    ul z; P p; PP q;\n";
+
+        assert_eq!(
+            expanded(code),
+            "struct P { int a; }; unsigned long z; struct P p; struct P * q;"
+        );
+    }
+
+    #[test]
+    fn recursive_macros_stop_expanding() {
+        assert_eq!(expanded("#define A B\n#define B A\nint A;\n"), "int A;");
+        assert_eq!(
+            expanded("#define f(x) f(x + 1)\nint y = f(1);\n"),
+            "int y = f(1 + 1);"
+        );
+        assert_eq!(
+            expanded("#define F(x) G(x)\n#define G(x) F(x)\nint y = F(1);\n"),
+            "int y = F(1);"
+        );
+    }
+
+    #[test]
+    fn deep_macro_chains_hit_the_depth_guard() {
+        let mut code: String = (0..100)
+            .map(|i| format!("#define M{i} M{}\n", i + 1))
+            .collect();
+        code.push_str("int v = M0;\n");
+
+        let result = expanded(&code);
+        let stopped_at: usize = result
+            .trim_start_matches("int v = M")
+            .trim_end_matches(';')
+            .parse()
+            .unwrap();
+
+        assert!(stopped_at > 1 && stopped_at < 100, "{result}");
+    }
+
+    #[test]
+    fn maps_spans_after_non_ascii_comments() {
+        let code = "/* ação: código\n   já revisado */\nint x = 1; // olá\nint yy = 2;\n";
+
+        assert_eq!(origin_of(code, "x"), ("x", 3));
+        assert_eq!(origin_of(code, "yy"), ("yy", 4));
+    }
+
+    #[test]
+    fn maps_expansions_to_the_macro_use() {
+        let code = "// configuração\n#define MSG \"olá\"\nint main() { puts(MSG); }\n";
+
+        assert_eq!(origin_of(code, "\"olá\""), ("MSG", 3));
+        assert_eq!(origin_of(code, "puts"), ("puts", 3));
+    }
+
+    #[test]
+    fn every_output_byte_has_an_origin() {
+        for code in [
+            "// ünïcödé\n#define SQ(x) ((x)*(x))\nint a = SQ(SQ(2)); /* ø */\n",
+            "typedef struct { int a; } P, *PP;\nPP p;\n",
+            "#ifdef X\nint a;\n#else\nint b;\n#endif\n",
+        ] {
+            let preprocessed = preprocess(code, Language::C);
+
+            assert_eq!(preprocessed.code.len(), preprocessed.origins.len());
+            assert!(
+                preprocessed
+                    .origins
+                    .iter()
+                    .all(|&(s, e)| s <= e && e <= code.len() && code.is_char_boundary(s))
+            );
+        }
+    }
+}
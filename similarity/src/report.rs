@@ -0,0 +1,88 @@
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetricBreakdown {
+    pub levenshtein: Option<f32>,
+    pub jaccard: Option<f32>,
+    pub lcs: Option<f32>,
+    pub cosine: Option<f32>,
+    pub combined: f32,
+}
+
+impl MetricBreakdown {
+    #[must_use]
+    pub fn values(&self) -> Vec<(&'static str, f32)> {
+        [
+            ("Levenshtein", self.levenshtein),
+            ("Jaccard", self.jaccard),
+            ("LCS", self.lcs),
+            ("Cosine", self.cosine),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| value.map(|v| (name, v)))
+        .collect()
+    }
+
+    #[must_use]
+    pub fn describe(&self) -> String {
+        self.values()
+            .iter()
+            .map(|(name, value)| format!("{} {:.2}%", name.to_uppercase(), value * 100.0))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionMatch {
+    pub left: String,
+    pub right: String,
+    pub metrics: MetricBreakdown,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FunctionReport {
+    pub score: f32,
+    pub matches: Vec<FunctionMatch>,
+}
+
+impl FunctionReport {
+    #[must_use]
+    pub fn closest(&self) -> Option<&FunctionMatch> {
+        self.matches.first()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SimilarityReport {
+    pub score: f32,
+    pub file: Option<MetricBreakdown>,
+    pub functions: Option<FunctionReport>,
+}
+
+impl SimilarityReport {
+    #[must_use]
+    pub fn from_score(score: f32) -> Self {
+        Self {
+            score,
+            ..Default::default()
+        }
+    }
+
+    #[must_use]
+    pub fn mirrored(&self) -> Self {
+        Self {
+            functions: self.functions.as_ref().map(|f| FunctionReport {
+                score: f.score,
+                matches: f
+                    .matches
+                    .iter()
+                    .map(|m| FunctionMatch {
+                        left: m.right.clone(),
+                        right: m.left.clone(),
+                        metrics: m.metrics.clone(),
+                    })
+                    .collect(),
+            }),
+            ..self.clone()
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use crate::Token;
+
+pub struct Lcg(u64);
+
+impl Lcg {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn below(&mut self, n: usize) -> usize {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((self.0 >> 33) % n as u64) as usize
+    }
+
+    pub fn tokens(&mut self, len: usize, alphabet: usize) -> Vec<Token> {
+        (0..len)
+            .map(|_| Token::Other(self.below(alphabet).to_string()))
+            .collect()
+    }
+
+    pub fn bytes(&mut self, len: usize, alphabet: u8) -> Vec<u8> {
+        (0..len)
+            .map(|_| b'a' + self.below(alphabet as usize) as u8)
+            .collect()
+    }
+}
@@ -0,0 +1,338 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::{AnalyzedFile, Token, config::WinnowingConfig};
+
+pub const MIN_PRUNED_POSTINGS: usize = 10;
+
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Fingerprint {
+    pub hash: u64,
+    pub position: usize,
+}
+
+fn fnv(hash: u64, bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(hash, |h, b| (h ^ *b as u64).wrapping_mul(FNV_PRIME))
+}
+
+fn token_hash(token: &Token) -> u64 {
+    let hash = fnv(FNV_OFFSET, token.as_str().as_bytes());
+
+    match token {
+        Token::Other(op) => fnv(hash, op.as_bytes()),
+        _ => hash,
+    }
+}
+
+fn kgram_hashes(tokens: &[Token], kgram: usize) -> Vec<u64> {
+    let hashes: Vec<u64> = tokens.iter().map(token_hash).collect();
+
+    hashes
+        .windows(kgram)
+        .map(|w| w.iter().fold(FNV_OFFSET, |h, t| fnv(h, &t.to_le_bytes())))
+        .collect()
+}
+
+pub fn fingerprint(tokens: &[Token], config: &WinnowingConfig) -> Vec<Fingerprint> {
+    let hashes = kgram_hashes(tokens, config.kgram);
+    let window = config.window;
+
+    if hashes.is_empty() {
+        return vec![];
+    }
+
+    if hashes.len() <= window {
+        let (position, hash) = hashes
+            .iter()
+            .enumerate()
+            .rev()
+            .min_by_key(|(_, h)| **h)
+            .unwrap();
+
+        return vec![Fingerprint {
+            hash: *hash,
+            position,
+        }];
+    }
+
+    let mut fingerprints: Vec<Fingerprint> = vec![];
+
+    for start in 0..=(hashes.len() - window) {
+        let (offset, hash) = hashes[start..start + window]
+            .iter()
+            .enumerate()
+            .rev()
+            .min_by_key(|(_, h)| **h)
+            .unwrap();
+
+        let position = start + offset;
+
+        if fingerprints.last().is_none_or(|f| f.position != position) {
+            fingerprints.push(Fingerprint {
+                hash: *hash,
+                position,
+            });
+        }
+    }
+
+    fingerprints
+}
+
+pub fn fingerprint_similarity(f1: &[Fingerprint], f2: &[Fingerprint]) -> f32 {
+    let set1: HashSet<u64> = f1.iter().map(|f| f.hash).collect();
+    let set2: HashSet<u64> = f2.iter().map(|f| f.hash).collect();
+
+    if set1.is_empty() || set2.is_empty() {
+        return 0.0;
+    }
+
+    let shared = set1.intersection(&set2).count();
+
+    2.0 * shared as f32 / (set1.len() + set2.len()) as f32
+}
+
+#[derive(Default)]
+pub struct FingerprintIndex {
+    postings: HashMap<u64, Vec<usize>>,
+    sizes: HashMap<usize, usize>,
+}
+
+impl FingerprintIndex {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, id: usize, file: &AnalyzedFile) {
+        let hashes: HashSet<u64> = file.fingerprints.iter().map(|f| f.hash).collect();
+
+        self.sizes.insert(id, hashes.len());
+
+        for hash in hashes {
+            self.postings.entry(hash).or_default().push(id);
+        }
+    }
+
+    pub fn candidate_pairs(&self, config: &WinnowingConfig) -> Vec<(usize, usize)> {
+        let max_postings = ((config.max_document_frequency * self.sizes.len() as f32).ceil()
+            as usize)
+            .max(MIN_PRUNED_POSTINGS);
+
+        let mut shared: HashMap<(usize, usize), usize> = HashMap::new();
+
+        for ids in self
+            .postings
+            .values()
+            .filter(|ids| ids.len() <= max_postings)
+        {
+            for (i, &a) in ids.iter().enumerate() {
+                for &b in &ids[i + 1..] {
+                    *shared.entry((a.min(b), a.max(b))).or_default() += 1;
+                }
+            }
+        }
+
+        let mut pairs: Vec<(usize, usize)> = shared
+            .into_iter()
+            .filter(|((a, b), n)| *n >= config.min_shared.min(self.sizes[a]).min(self.sizes[b]))
+            .map(|(p, _)| p)
+            .collect();
+
+        pairs.sort_unstable();
+        pairs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Language, test_support::Lcg};
+
+    fn config(kgram: usize, window: usize) -> WinnowingConfig {
+        WinnowingConfig {
+            kgram,
+            window,
+            ..WinnowingConfig::default()
+        }
+    }
+
+    fn naive_fingerprint(tokens: &[Token], config: &WinnowingConfig) -> Vec<Fingerprint> {
+        let hashes = kgram_hashes(tokens, config.kgram);
+        let mut selected: Vec<Fingerprint> = vec![];
+
+        if hashes.is_empty() {
+            return selected;
+        }
+
+        let window = config.window.min(hashes.len());
+
+        for start in 0..=hashes.len() - window {
+            let min = *hashes[start..start + window].iter().min().unwrap();
+            let offset = (0..window)
+                .rev()
+                .find(|o| hashes[start + o] == min)
+                .unwrap();
+
+            let fingerprint = Fingerprint {
+                hash: min,
+                position: start + offset,
+            };
+
+            if !selected.contains(&fingerprint) {
+                selected.push(fingerprint);
+            }
+        }
+
+        selected
+    }
+
+    fn file(tokens: &[Token], config: &WinnowingConfig) -> AnalyzedFile {
+        AnalyzedFile {
+            file_tokens: tokens.to_vec(),
+            file_spans: vec![],
+            functions: HashMap::new(),
+            fingerprints: fingerprint(tokens, config),
+            discounted: 0.0,
+            language: Language::C,
+            removed_at: vec![],
+        }
+    }
+
+    #[test]
+    fn selects_rightmost_minimum_of_every_window() {
+        let mut rng = Lcg::new(2);
+
+        for (kgram, window) in [(1, 1), (3, 2), (5, 4), (12, 8)] {
+            let config = config(kgram, window);
+
+            for len in [0, kgram - 1, kgram, kgram + window, 50, 300] {
+                let tokens = rng.tokens(len, 4);
+
+                assert_eq!(
+                    fingerprint(&tokens, &config),
+                    naive_fingerprint(&tokens, &config),
+                    "k={kgram} w={window} n={len}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn shared_run_of_guarantee_length_shares_a_fingerprint() {
+        let mut rng = Lcg::new(3);
+        let config = WinnowingConfig::default();
+        let guarantee = config.window + config.kgram - 1;
+
+        for _ in 0..50 {
+            let shared = rng.tokens(guarantee, 6);
+            let mut noise = || {
+                let len = rng.below(40);
+                rng.tokens(len, 6)
+            };
+
+            let a = [noise(), shared.clone(), noise()].concat();
+            let b = [noise(), shared, noise()].concat();
+
+            let (fa, fb) = (fingerprint(&a, &config), fingerprint(&b, &config));
+            assert!(fingerprint_similarity(&fa, &fb) > 0.0);
+        }
+    }
+
+    #[test]
+    fn candidate_pairs_match_pairwise_counting() {
+        let mut rng = Lcg::new(4);
+        let config = WinnowingConfig {
+            kgram: 3,
+            window: 2,
+            min_shared: 3,
+            max_document_frequency: 0.3,
+        };
+
+        let base = rng.tokens(60, 5);
+        let files: Vec<AnalyzedFile> = (0..40)
+            .map(|_| {
+                let mut tokens = base.clone();
+                for _ in 0..rng.below(30) {
+                    let at = rng.below(tokens.len());
+                    tokens[at] = Token::Other(rng.below(5).to_string());
+                }
+                file(&tokens, &config)
+            })
+            .collect();
+
+        let mut index = FingerprintIndex::new();
+        for (id, f) in files.iter().enumerate() {
+            index.insert(id, f);
+        }
+
+        let sets: Vec<HashSet<u64>> = files
+            .iter()
+            .map(|f| f.fingerprints.iter().map(|f| f.hash).collect())
+            .collect();
+
+        let max_postings = ((0.3 * files.len() as f32).ceil() as usize).max(MIN_PRUNED_POSTINGS);
+        let mut expected = vec![];
+
+        for a in 0..files.len() {
+            for b in a + 1..files.len() {
+                let shared = sets[a]
+                    .intersection(&sets[b])
+                    .filter(|h| sets.iter().filter(|s| s.contains(h)).count() <= max_postings)
+                    .count();
+
+                let needed = config.min_shared.min(sets[a].len()).min(sets[b].len());
+
+                if shared > 0 && shared >= needed {
+                    expected.push((a, b));
+                }
+            }
+        }
+
+        assert!(!expected.is_empty());
+        assert_eq!(index.candidate_pairs(&config), expected);
+    }
+
+    fn shared_boilerplate(files: usize, copied: usize) -> Vec<(usize, usize)> {
+        let config = config(3, 2);
+        let boilerplate: Vec<Token> = (0..10).map(|i| Token::Other(i.to_string())).collect();
+
+        let mut index = FingerprintIndex::new();
+        for id in 0..files {
+            let mut tokens = boilerplate.clone();
+            let author = if id < copied { 0 } else { id };
+            tokens.extend((0..10).map(|i| Token::Other(format!("{author}-{i}"))));
+            index.insert(id, &file(&tokens, &config));
+        }
+
+        index.candidate_pairs(&config)
+    }
+
+    #[test]
+    fn fingerprints_in_almost_every_file_are_pruned() {
+        let pairs = shared_boilerplate(40, 0);
+
+        assert!(pairs.is_empty());
+    }
+
+    #[test]
+    fn copying_in_small_classes_is_never_pruned() {
+        let pairs = shared_boilerplate(5, 3);
+
+        assert!(pairs.contains(&(0, 1)));
+        assert!(pairs.contains(&(0, 2)));
+        assert!(pairs.contains(&(1, 2)));
+    }
+
+    #[test]
+    fn large_rings_are_still_candidates() {
+        let pairs = shared_boilerplate(40, 20);
+
+        assert!(pairs.contains(&(0, 19)));
+        assert!(!pairs.contains(&(20, 21)));
+    }
+}